@@ -0,0 +1,256 @@
+//! Reader for btsnoop captures of HCI traffic, as written by `btmon` and Android's HCI snoop
+//! log.
+//!
+//! These captures hold the advertising reports a scanning controller handed to the host
+//! rather than raw link layer packets, so they can be recorded with any controller.
+
+use chrono::{DateTime, Utc};
+
+use super::{CaptureError, CapturedFrame};
+use crate::codec::decode::DecodeError;
+use crate::transport::bluetooth::decode_advertising_data;
+use crate::transport::hci::{advertising_reports, AdvertisingReports, H4_EVENT};
+
+/// Unencapsulated HCI packets, the record flags tell commands and events apart
+pub const DATALINK_H1: u32 = 1001;
+
+/// HCI packets preceded by the UART packet indicator
+pub const DATALINK_H4: u32 = 1002;
+
+/// Linux monitor packets, the record flags carry the monitor opcode. Written by `btmon`.
+pub const DATALINK_MONITOR: u32 = 2001;
+
+const MAGIC: &[u8; 8] = b"btsnoop\0";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+const RECORD_HEADER_SIZE: usize = 24;
+
+/// Timestamps count microseconds from midnight, January 1st, 0 AD
+const UNIX_EPOCH_MICROS: i64 = 0x00DC_DDB3_0F2F_8000;
+
+/// H1 record flags of a received command or event packet
+const FLAGS_H1_EVENT: u32 = 0b11;
+
+/// Monitor opcode of an HCI event
+const MONITOR_EVENT: u32 = 3;
+
+/// An HCI event of the capture
+struct Event<'a> {
+    timestamp: DateTime<Utc>,
+    data: &'a [u8],
+}
+
+/// Iterator over the Remote ID frames of a btsnoop capture.
+///
+/// The advertising data of each LE Advertising Report and LE Extended Advertising Report is
+/// decoded, other packets are skipped. Extended advertising data split over several reports is
+/// not reassembled, incomplete reports are skipped as well. So are reports without Remote ID
+/// service data, including those whose AD structures are malformed ahead of it.
+///
+/// A frame that cannot be decoded yields an error and the iteration carries on with the next
+/// report. A malformed capture yields an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct BtsnoopReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    datalink: u32,
+    /// Reports of the current event that have not been looked at yet
+    reports: Option<(DateTime<Utc>, AdvertisingReports<'a>)>,
+}
+
+impl<'a> BtsnoopReader<'a> {
+    /// Read the file header of a btsnoop capture
+    pub fn new(data: &'a [u8]) -> Result<Self, CaptureError> {
+        let header = data
+            .get(..HEADER_SIZE)
+            .ok_or(CaptureError::Malformed { offset: 0 })?;
+        if &header[..8] != MAGIC {
+            let magic = u32::from_be_bytes(header[..4].try_into().unwrap());
+            return Err(CaptureError::UnknownFormat(magic));
+        }
+
+        let version = u32::from_be_bytes(header[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(CaptureError::Malformed { offset: 8 });
+        }
+
+        let datalink = u32::from_be_bytes(header[12..16].try_into().unwrap());
+        if !matches!(datalink, DATALINK_H1 | DATALINK_H4 | DATALINK_MONITOR) {
+            return Err(CaptureError::UnsupportedLinkType(datalink));
+        }
+
+        Ok(Self {
+            data,
+            offset: HEADER_SIZE,
+            datalink,
+            reports: None,
+        })
+    }
+
+    /// Read the next HCI event, `None` at the end of the capture
+    fn next_event(&mut self) -> Result<Option<Event<'a>>, CaptureError> {
+        while self.offset < self.data.len() {
+            let offset = self.offset;
+            let header = self
+                .data
+                .get(offset..offset + RECORD_HEADER_SIZE)
+                .ok_or(CaptureError::Malformed { offset })?;
+            let field = |i: usize| u32::from_be_bytes(header[i..i + 4].try_into().unwrap());
+
+            // original length, included length, flags, cumulative drops, timestamp
+            let len = field(4) as usize;
+            let flags = field(8);
+            let timestamp = i64::from_be_bytes(header[16..24].try_into().unwrap());
+
+            let start = offset + RECORD_HEADER_SIZE;
+            let packet = start
+                .checked_add(len)
+                .and_then(|end| self.data.get(start..end))
+                .ok_or(CaptureError::Malformed { offset })?;
+            self.offset = start + len;
+
+            let event = match self.datalink {
+                DATALINK_H1 if flags & FLAGS_H1_EVENT == FLAGS_H1_EVENT => Some(packet),
+                DATALINK_H4 => packet
+                    .split_first()
+                    .filter(|(&indicator, _)| indicator == H4_EVENT)
+                    .map(|(_, event)| event),
+                DATALINK_MONITOR if flags & 0xFFFF == MONITOR_EVENT => Some(packet),
+                _ => None,
+            };
+
+            if let Some(data) = event {
+                let timestamp = timestamp
+                    .checked_sub(UNIX_EPOCH_MICROS)
+                    .and_then(DateTime::from_timestamp_micros)
+                    .ok_or(CaptureError::Decode(DecodeError::InvalidTimestamp))?;
+                return Ok(Some(Event { timestamp, data }));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for BtsnoopReader<'a> {
+    type Item = Result<CapturedFrame<'a>, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some((timestamp, reports)) = &mut self.reports else {
+                let event = match self.next_event() {
+                    Ok(event) => event?,
+                    Err(e) => {
+                        self.offset = self.data.len();
+                        return Some(Err(e));
+                    }
+                };
+
+                match advertising_reports(event.data) {
+                    Ok(reports) => {
+                        self.reports = reports.map(|reports| (event.timestamp, reports));
+                    }
+                    Err(e) => return Some(Err(e.into())),
+                }
+                continue;
+            };
+
+            let report = match reports.next() {
+                Some(Ok(report)) => report,
+                Some(Err(e)) => return Some(Err(e.into())),
+                None => {
+                    self.reports = None;
+                    continue;
+                }
+            };
+            if !report.is_complete() {
+                continue;
+            }
+
+            match decode_advertising_data(report.data) {
+                Ok(Some((counter, payload))) => {
+                    return Some(Ok(CapturedFrame {
+                        timestamp: *timestamp,
+                        source: report.address,
+                        rssi: report.rssi,
+                        counter,
+                        payload,
+                    }))
+                }
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::data::fixtures::basic_id;
+    use crate::transport::Payload;
+
+    // generated by tests/fixtures/generate.py
+    const BTSNOOP: &[u8] = include_bytes!("../../tests/fixtures/remote_id.btsnoop");
+
+    #[test]
+    fn read_btsnoop() {
+        let frames: Vec<_> = BtsnoopReader::new(BTSNOOP)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(2, frames.len());
+
+        let start = DateTime::from_timestamp(1_714_816_800, 0).unwrap();
+        let address = [0xC0, 0xFF, 0xEE, 0x12, 0x34, 0x56];
+
+        let frame = &frames[0];
+        assert_eq!(start + chrono::Duration::milliseconds(125), frame.timestamp);
+        assert_eq!(address, frame.source);
+        assert_eq!(Some(-60), frame.rssi);
+        assert_eq!(7, frame.counter);
+        assert!(matches!(
+            &frame.payload,
+            Payload::Message(message) if *message == basic_id()
+        ));
+
+        let frame = &frames[1];
+        assert_eq!(start + chrono::Duration::milliseconds(375), frame.timestamp);
+        assert_eq!(address, frame.source);
+        assert_eq!(Some(-71), frame.rssi);
+        assert_eq!(8, frame.counter);
+        let Payload::Pack(pack) = frame.payload.clone() else {
+            panic!("expected a Message Pack, got {:?}", frame.payload);
+        };
+        let messages: Vec<_> = pack.map(Result::unwrap).collect();
+        assert_eq!(2, messages.len());
+        assert_eq!(basic_id(), messages[0]);
+    }
+
+    #[test]
+    fn unsupported_capture() {
+        let mut header = BTSNOOP[..HEADER_SIZE].to_vec();
+        // BCSP
+        header[12..16].copy_from_slice(&1003u32.to_be_bytes());
+        assert_eq!(
+            Some(CaptureError::UnsupportedLinkType(1003)),
+            BtsnoopReader::new(&header).err()
+        );
+
+        assert_eq!(
+            Some(CaptureError::UnknownFormat(0xA1B2_C3D4)),
+            BtsnoopReader::new(&[0xA1, 0xB2, 0xC3, 0xD4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).err()
+        );
+
+        // the last record is cut short
+        let mut reader = BtsnoopReader::new(&BTSNOOP[..BTSNOOP.len() - 1]).unwrap();
+        assert!(reader
+            .by_ref()
+            .any(|frame| matches!(frame, Err(CaptureError::Malformed { .. }))));
+        assert!(reader.next().is_none());
+    }
+}
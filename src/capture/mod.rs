@@ -0,0 +1,68 @@
+//! Readers for capture files of Remote ID broadcasts.
+//!
+//! The readers work on a capture loaded into memory and yield a [`CapturedFrame`] for every
+//! Remote ID broadcast they find, skipping all other packets.
+
+use chrono::{DateTime, Utc};
+
+use crate::codec::decode::DecodeError;
+use crate::transport::Payload;
+
+pub mod btsnoop;
+#[cfg(feature = "pcap")]
+pub mod pcap;
+
+/// A Remote ID frame found in a capture
+#[derive(Debug, Clone)]
+pub struct CapturedFrame<'a> {
+    /// Time the packet was captured
+    pub timestamp: DateTime<Utc>,
+    /// MAC address of the transmitter, in the order it is usually written
+    pub source: [u8; 6],
+    /// Received signal strength in dBm, when the capture recorded it
+    pub rssi: Option<i8>,
+    pub counter: u8,
+    pub payload: Payload<'a>,
+}
+
+/// Reasons a capture could not be read
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CaptureError {
+    /// The data does not start with the magic number of a supported format
+    UnknownFormat(u32),
+    /// The capture holds packets of a link type the reader cannot interpret
+    UnsupportedLinkType(u32),
+    /// A record or block runs past the end of the capture or has an invalid length
+    Malformed { offset: usize },
+    /// A pcapng section describes more interfaces than the reader can keep track of
+    TooManyInterfaces,
+    /// A pcapng packet refers to an interface that was not described
+    UnknownInterface(u32),
+    /// A Remote ID frame in the capture could not be decoded
+    Decode(DecodeError),
+}
+
+impl core::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CaptureError::UnknownFormat(magic) => {
+                write!(f, "unknown capture format: {magic:#010x}")
+            }
+            CaptureError::UnsupportedLinkType(link_type) => {
+                write!(f, "unsupported link type: {link_type}")
+            }
+            CaptureError::Malformed { offset } => write!(f, "malformed capture at byte {offset}"),
+            CaptureError::TooManyInterfaces => write!(f, "too many capture interfaces"),
+            CaptureError::UnknownInterface(id) => write!(f, "unknown capture interface: {id}"),
+            CaptureError::Decode(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl core::error::Error for CaptureError {}
+
+impl From<DecodeError> for CaptureError {
+    fn from(value: DecodeError) -> Self {
+        CaptureError::Decode(value)
+    }
+}
@@ -0,0 +1,634 @@
+//! Reader for pcap and pcapng captures of Remote ID broadcasts.
+//!
+//! Works on a capture loaded into memory and walks its packets, yielding the Remote ID
+//! frames found in Bluetooth LE link layer captures with the nRF sniffer pseudo-header
+//! (`LINKTYPE_BLUETOOTH_LE_LL_WITH_PHDR`) and 802.11 monitor mode captures with a radiotap
+//! header (`LINKTYPE_IEEE802_11_RADIOTAP`). Packets of other link types, and packets that do
+//! not carry Remote ID, are skipped.
+
+use chrono::{DateTime, Utc};
+
+use super::{CaptureError, CapturedFrame};
+use crate::codec::decode::DecodeError;
+use crate::get_bits;
+use crate::transport::bluetooth::decode_advertising_data;
+use crate::transport::wifi::beacon::decode_beacon_frame;
+use crate::transport::wifi::nan::decode_nan_frame;
+use crate::transport::wifi::MANAGEMENT_HEADER_SIZE;
+use crate::transport::Payload;
+
+/// 802.11 frames preceded by a radiotap header
+pub const LINKTYPE_IEEE802_11_RADIOTAP: u32 = 127;
+
+/// Bluetooth LE link layer packets preceded by the nRF sniffer pseudo-header
+pub const LINKTYPE_BLUETOOTH_LE_LL_WITH_PHDR: u32 = 256;
+
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+const PCAP_HEADER_SIZE: usize = 24;
+const PCAP_RECORD_HEADER_SIZE: usize = 16;
+
+const BLOCK_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const BLOCK_INTERFACE_DESCRIPTION: u32 = 0x1;
+const BLOCK_ENHANCED_PACKET: u32 = 0x6;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+const OPTION_END: u16 = 0;
+const OPTION_IF_TSRESOL: u16 = 9;
+
+/// Interfaces of a pcapng section the reader can keep track of
+pub const MAX_INTERFACES: usize = 8;
+
+const LE_PSEUDO_HEADER_SIZE: usize = 10;
+const LE_FLAG_SIGNAL_POWER_VALID: u16 = 0x0002;
+const LE_FLAG_CRC_CHECKED: u16 = 0x0400;
+const LE_FLAG_CRC_VALID: u16 = 0x0800;
+const LE_PHY_CODED: u16 = 2;
+const ADVERTISING_ACCESS_ADDRESS: u32 = 0x8E89_BED6;
+
+const PDU_ADV_IND: u8 = 0x0;
+const PDU_ADV_NONCONN_IND: u8 = 0x2;
+const PDU_SCAN_RSP: u8 = 0x4;
+const PDU_ADV_SCAN_IND: u8 = 0x6;
+/// ADV_EXT_IND on the primary channels, AUX_ADV_IND and friends on the secondary ones
+const PDU_ADV_EXT: u8 = 0x7;
+const EXTENDED_HEADER_ADV_A: u8 = 0x01;
+
+const RADIOTAP_FLAGS: usize = 1;
+const RADIOTAP_ANTENNA_SIGNAL: usize = 5;
+const RADIOTAP_EXTENDED_PRESENT: u32 = 1 << 31;
+const RADIOTAP_FLAG_FCS: u8 = 0x10;
+/// Size and alignment of the radiotap fields up to the antenna signal
+const RADIOTAP_FIELDS: [(usize, usize); 6] = [
+    // TSFT
+    (8, 8),
+    // Flags
+    (1, 1),
+    // Rate
+    (1, 1),
+    // Channel
+    (4, 2),
+    // FHSS
+    (2, 1),
+    // Antenna signal in dBm
+    (1, 1),
+];
+const FCS_SIZE: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Interface {
+    link_type: u32,
+    units_per_second: u64,
+}
+
+const UNUSED_INTERFACE: Interface = Interface {
+    link_type: 0,
+    units_per_second: 1_000_000,
+};
+
+/// A raw packet of the capture
+struct Packet<'a> {
+    timestamp: DateTime<Utc>,
+    link_type: u32,
+    data: &'a [u8],
+}
+
+/// Iterator over the Remote ID frames of a pcap or pcapng capture.
+///
+/// A frame that cannot be decoded yields an error and the iteration carries on with the next
+/// packet. A malformed capture yields an error and ends the iteration.
+/// ```
+/// # use remote_id::capture::pcap::PcapReader;
+/// // pcap header, microsecond timestamps, no packets
+/// let capture = [
+///     0xD4, 0xC3, 0xB2, 0xA1, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xFF, 0xFF, 0, 0, 0, 1, 0, 0,
+/// ];
+/// assert_eq!(0, PcapReader::new(&capture).unwrap().count());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PcapReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+    big_endian: bool,
+    /// pcapng when set, a pcap capture describes its only interface in the file header
+    pcapng: bool,
+    interfaces: [Interface; MAX_INTERFACES],
+    interface_count: usize,
+}
+
+impl<'a> PcapReader<'a> {
+    /// Read the file header of a pcap or pcapng capture
+    pub fn new(data: &'a [u8]) -> Result<Self, CaptureError> {
+        let magic = data
+            .get(..4)
+            .ok_or(CaptureError::Malformed { offset: 0 })?
+            .try_into()
+            .unwrap();
+
+        let mut reader = Self {
+            data,
+            offset: 0,
+            big_endian: false,
+            pcapng: true,
+            interfaces: [UNUSED_INTERFACE; MAX_INTERFACES],
+            interface_count: 0,
+        };
+
+        // the section header block type reads the same in both byte orders, the block itself
+        // is handled while iterating
+        if u32::from_le_bytes(magic) == BLOCK_SECTION_HEADER {
+            return Ok(reader);
+        }
+
+        let units_per_second = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAP_MAGIC_MICROS, _) | (_, PCAP_MAGIC_MICROS) => 1_000_000,
+            (PCAP_MAGIC_NANOS, _) | (_, PCAP_MAGIC_NANOS) => 1_000_000_000,
+            _ => return Err(CaptureError::UnknownFormat(u32::from_be_bytes(magic))),
+        };
+        reader.big_endian = u32::from_be_bytes(magic) >> 16 == 0xA1B2;
+
+        reader.bytes(0, PCAP_HEADER_SIZE)?;
+        reader.pcapng = false;
+        reader.interfaces[0] = Interface {
+            link_type: reader.u32(20)?,
+            units_per_second,
+        };
+        reader.interface_count = 1;
+        reader.offset = PCAP_HEADER_SIZE;
+
+        Ok(reader)
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8], CaptureError> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(offset..end))
+            .ok_or(CaptureError::Malformed { offset })
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, CaptureError> {
+        let bytes = self.bytes(offset, 2)?.try_into().unwrap();
+        Ok(match self.big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, CaptureError> {
+        let bytes = self.bytes(offset, 4)?.try_into().unwrap();
+        Ok(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    /// Read the next packet, `None` at the end of the capture
+    fn next_packet(&mut self) -> Result<Option<Packet<'a>>, CaptureError> {
+        loop {
+            if self.offset >= self.data.len() {
+                return Ok(None);
+            }
+
+            if self.pcapng {
+                if let Some(packet) = self.next_block()? {
+                    return Ok(Some(packet));
+                }
+                continue;
+            }
+
+            let offset = self.offset;
+            let interface = self.interfaces[0];
+            let seconds = self.u32(offset)? as u64;
+            let fraction = self.u32(offset + 4)? as u64;
+            let len = self.u32(offset + 8)? as usize;
+            let data = self
+                .bytes(offset + PCAP_RECORD_HEADER_SIZE, len)
+                .map_err(|_| CaptureError::Malformed { offset })?;
+            self.offset += PCAP_RECORD_HEADER_SIZE + len;
+
+            return Ok(Some(Packet {
+                timestamp: timestamp(
+                    seconds * interface.units_per_second + fraction,
+                    interface.units_per_second,
+                )?,
+                link_type: interface.link_type,
+                data,
+            }));
+        }
+    }
+
+    /// Read the next pcapng block, returning the packet if it holds one
+    fn next_block(&mut self) -> Result<Option<Packet<'a>>, CaptureError> {
+        let offset = self.offset;
+        let block_type = self.u32(offset)?;
+
+        if block_type == BLOCK_SECTION_HEADER {
+            let magic = self.bytes(offset + 8, 4)?.try_into().unwrap();
+            self.big_endian = match u32::from_le_bytes(magic) {
+                BYTE_ORDER_MAGIC => false,
+                _ if u32::from_be_bytes(magic) == BYTE_ORDER_MAGIC => true,
+                _ => return Err(CaptureError::Malformed { offset }),
+            };
+            // interfaces are numbered per section
+            self.interface_count = 0;
+        }
+
+        // type, length, body, length again
+        let len = self.u32(offset + 4)? as usize;
+        if len < 12 || !len.is_multiple_of(4) {
+            return Err(CaptureError::Malformed { offset });
+        }
+        let body = self.bytes(offset + 8, len - 12)?;
+        self.offset += len;
+
+        match block_type {
+            BLOCK_INTERFACE_DESCRIPTION => {
+                if self.interface_count == MAX_INTERFACES {
+                    return Err(CaptureError::TooManyInterfaces);
+                }
+                self.interfaces[self.interface_count] = Interface {
+                    link_type: self.u16(offset + 8)? as u32,
+                    units_per_second: self.resolution(offset + 16, offset + len - 4)?,
+                };
+                self.interface_count += 1;
+
+                Ok(None)
+            }
+            BLOCK_ENHANCED_PACKET => {
+                let id = self.u32(offset + 8)?;
+                let interface = self.interfaces[..self.interface_count]
+                    .get(id as usize)
+                    .copied()
+                    .ok_or(CaptureError::UnknownInterface(id))?;
+
+                let high = self.u32(offset + 12)? as u64;
+                let low = self.u32(offset + 16)? as u64;
+                let captured_len = self.u32(offset + 20)? as usize;
+                let data = body
+                    .get(20..)
+                    .and_then(|data| data.get(..captured_len))
+                    .ok_or(CaptureError::Malformed { offset })?;
+
+                Ok(Some(Packet {
+                    timestamp: timestamp(high << 32 | low, interface.units_per_second)?,
+                    link_type: interface.link_type,
+                    data,
+                }))
+            }
+            // name resolution, statistics, custom blocks and the like
+            _ => Ok(None),
+        }
+    }
+
+    /// Timestamp units of an interface, read from the `if_tsresol` option between `start`
+    /// and `end`
+    fn resolution(&self, mut offset: usize, end: usize) -> Result<u64, CaptureError> {
+        while offset + 4 <= end {
+            let code = self.u16(offset)?;
+            let len = self.u16(offset + 2)? as usize;
+            if code == OPTION_END {
+                break;
+            }
+
+            if code == OPTION_IF_TSRESOL && len == 1 {
+                let resolution = self.bytes(offset + 4, 1)?[0];
+                let exponent = get_bits!(resolution, 6..0) as u32;
+                let units = match resolution & 0x80 {
+                    0 => 10u64.checked_pow(exponent),
+                    _ => 2u64.checked_pow(exponent),
+                };
+                return units.ok_or(CaptureError::Malformed { offset });
+            }
+
+            // values are padded to 32 bits
+            offset += 4 + len.next_multiple_of(4);
+        }
+
+        Ok(1_000_000)
+    }
+}
+
+impl<'a> Iterator for PcapReader<'a> {
+    type Item = Result<CapturedFrame<'a>, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let packet = match self.next_packet() {
+                Ok(packet) => packet?,
+                Err(e) => {
+                    self.offset = self.data.len();
+                    return Some(Err(e));
+                }
+            };
+
+            let frame = match packet.link_type {
+                LINKTYPE_BLUETOOTH_LE_LL_WITH_PHDR => {
+                    decode_le_packet(packet.timestamp, packet.data)
+                }
+                LINKTYPE_IEEE802_11_RADIOTAP => {
+                    decode_radiotap_packet(packet.timestamp, packet.data)
+                }
+                _ => Ok(None),
+            };
+
+            match frame {
+                Ok(Some(frame)) => return Some(Ok(frame)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+fn timestamp(units: u64, units_per_second: u64) -> Result<DateTime<Utc>, CaptureError> {
+    let seconds = units / units_per_second;
+    let nanos = (units % units_per_second) as u128 * 1_000_000_000 / units_per_second as u128;
+
+    i64::try_from(seconds)
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, nanos as u32))
+        .ok_or(CaptureError::Decode(DecodeError::InvalidTimestamp))
+}
+
+/// Decode a Bluetooth LE link layer packet preceded by the nRF sniffer pseudo-header
+fn decode_le_packet(
+    timestamp: DateTime<Utc>,
+    packet: &[u8],
+) -> Result<Option<CapturedFrame<'_>>, DecodeError> {
+    let Some((header, packet)) = packet.split_at_checked(LE_PSEUDO_HEADER_SIZE) else {
+        return Ok(None);
+    };
+
+    let flags = u16::from_le_bytes([header[8], header[9]]);
+    if flags & LE_FLAG_CRC_CHECKED != 0 && flags & LE_FLAG_CRC_VALID == 0 {
+        return Ok(None);
+    }
+    let rssi = (flags & LE_FLAG_SIGNAL_POWER_VALID != 0).then_some(header[1] as i8);
+
+    let Some((access_address, mut pdu)) = packet.split_at_checked(4) else {
+        return Ok(None);
+    };
+    if u32::from_le_bytes(access_address.try_into().unwrap()) != ADVERTISING_ACCESS_ADDRESS {
+        return Ok(None);
+    }
+    // the coding indicator precedes the PDU on the coded PHY
+    if get_bits!(flags, 15..14) == LE_PHY_CODED {
+        pdu = pdu.get(1..).unwrap_or_default();
+    }
+
+    let Some(payload) = pdu.get(2..).and_then(|p| p.get(..pdu[1] as usize)) else {
+        return Ok(None);
+    };
+
+    let (adv_a, adv_data) = match get_bits!(pdu[0], 3..0) {
+        PDU_ADV_IND | PDU_ADV_NONCONN_IND | PDU_SCAN_RSP | PDU_ADV_SCAN_IND => {
+            match payload.split_at_checked(6) {
+                Some((adv_a, adv_data)) => (adv_a, adv_data),
+                None => return Ok(None),
+            }
+        }
+        PDU_ADV_EXT => match extended_advertising_payload(payload) {
+            Some(payload) => payload,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let Some((counter, payload)) = decode_advertising_data(adv_data)? else {
+        return Ok(None);
+    };
+
+    // device addresses are sent least significant byte first
+    let mut source: [u8; 6] = adv_a.try_into().unwrap();
+    source.reverse();
+
+    Ok(Some(CapturedFrame {
+        timestamp,
+        source,
+        rssi,
+        counter,
+        payload,
+    }))
+}
+
+/// Split the payload of an extended advertising PDU into its advertiser address and
+/// advertising data, `None` when it carries no address
+fn extended_advertising_payload(payload: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&first, rest) = payload.split_first()?;
+    let (header, adv_data) = rest.split_at_checked(get_bits!(first, 5..0) as usize)?;
+
+    // the advertiser address is the first of the optional fields
+    let (&flags, fields) = header.split_first()?;
+    if flags & EXTENDED_HEADER_ADV_A == 0 {
+        return None;
+    }
+
+    Some((fields.get(..6)?, adv_data))
+}
+
+/// Decode an 802.11 frame preceded by a radiotap header
+fn decode_radiotap_packet(
+    timestamp: DateTime<Utc>,
+    packet: &[u8],
+) -> Result<Option<CapturedFrame<'_>>, DecodeError> {
+    let Some(len) = packet.get(2..4) else {
+        return Ok(None);
+    };
+    let Some((header, mut frame)) =
+        packet.split_at_checked(u16::from_le_bytes([len[0], len[1]]) as usize)
+    else {
+        return Ok(None);
+    };
+
+    let read_u32 = |offset: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(
+            header.get(offset..offset + 4)?.try_into().unwrap(),
+        ))
+    };
+    let Some(present) = read_u32(4) else {
+        return Ok(None);
+    };
+
+    // fields follow the last present word
+    let mut offset = 8;
+    let mut word = present;
+    while word & RADIOTAP_EXTENDED_PRESENT != 0 {
+        let Some(next) = read_u32(offset) else {
+            return Ok(None);
+        };
+        word = next;
+        offset += 4;
+    }
+
+    let mut flags = 0;
+    let mut rssi = None;
+    for (bit, &(size, alignment)) in RADIOTAP_FIELDS.iter().enumerate() {
+        if present & (1 << bit) == 0 {
+            continue;
+        }
+
+        offset = offset.next_multiple_of(alignment);
+        let Some(field) = header.get(offset..offset + size) else {
+            return Ok(None);
+        };
+        match bit {
+            RADIOTAP_FLAGS => flags = field[0],
+            RADIOTAP_ANTENNA_SIGNAL => rssi = Some(field[0] as i8),
+            _ => {}
+        }
+        offset += size;
+    }
+
+    if flags & RADIOTAP_FLAG_FCS != 0 {
+        frame = &frame[..frame.len().saturating_sub(FCS_SIZE)];
+    }
+
+    // only management frames can carry Remote ID, control frames are shorter than the header
+    if frame.len() < MANAGEMENT_HEADER_SIZE || frame[0] & 0x0F != 0 {
+        return Ok(None);
+    }
+
+    let remote_id = match decode_beacon_frame(frame)? {
+        Some(remote_id) => Some(remote_id),
+        None => decode_nan_frame(frame)?,
+    };
+
+    Ok(remote_id.map(|remote_id| CapturedFrame {
+        timestamp,
+        source: remote_id.source,
+        rssi,
+        counter: remote_id.counter,
+        payload: Payload::Pack(remote_id.pack),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::data::fixtures::basic_id;
+    use crate::data::RemoteIDMessage;
+
+    // generated by tests/fixtures/generate.py
+    const PCAPNG: &[u8] = include_bytes!("../../tests/fixtures/remote_id.pcapng");
+    const PCAP: &[u8] = include_bytes!("../../tests/fixtures/remote_id.pcap");
+
+    const BLE_ADDRESS: [u8; 6] = [0xC0, 0xFF, 0xEE, 0x12, 0x34, 0x56];
+    const WIFI_ADDRESS: [u8; 6] = [0x02, 0x42, 0xAC, 0x11, 0x00, 0x02];
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(1_714_816_800_000 + millis).unwrap()
+    }
+
+    fn messages(payload: Payload<'_>) -> Vec<RemoteIDMessage> {
+        match payload {
+            Payload::Message(message) => std::vec![message],
+            Payload::Pack(pack) => pack.map(Result::unwrap).collect(),
+        }
+    }
+
+    #[test]
+    fn read_pcapng() {
+        let frames: Vec<_> = PcapReader::new(PCAPNG)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(4, frames.len());
+
+        let summary: Vec<_> = frames
+            .iter()
+            .map(|f| (f.timestamp, f.source, f.rssi, f.counter))
+            .collect();
+        assert_eq!(
+            std::vec![
+                (at(125), BLE_ADDRESS, Some(-60), 7),
+                (
+                    at(375) + chrono::Duration::microseconds(400),
+                    BLE_ADDRESS,
+                    Some(-71),
+                    8
+                ),
+                (at(500), WIFI_ADDRESS, Some(-55), 9),
+                (at(750), WIFI_ADDRESS, Some(-48), 10),
+            ],
+            summary
+        );
+
+        let mut frames = frames.into_iter();
+        assert!(matches!(
+            frames.next().unwrap().payload,
+            Payload::Message(message) if message == basic_id()
+        ));
+
+        let pack = messages(frames.next().unwrap().payload);
+        assert_eq!(2, pack.len());
+        assert_eq!(basic_id(), pack[0]);
+        let RemoteIDMessage::SelfId(self_id) = &pack[1] else {
+            panic!("expected a Self ID message, got {:?}", pack[1]);
+        };
+        assert_eq!(Some("Bridge survey"), self_id.text());
+
+        let pack = messages(frames.next().unwrap().payload);
+        let [RemoteIDMessage::OperatorId(operator_id)] = pack.as_slice() else {
+            panic!("expected an Operator ID message, got {pack:?}");
+        };
+        assert_eq!(b"FIN87astrdge12k8\0\0\0\0", &operator_id.operator_id);
+
+        assert_eq!(
+            std::vec![basic_id()],
+            messages(frames.next().unwrap().payload)
+        );
+    }
+
+    #[test]
+    fn read_pcap() {
+        let mut reader = PcapReader::new(PCAP).unwrap();
+
+        let frame = reader.next().unwrap().unwrap();
+        assert_eq!(at(125), frame.timestamp);
+        assert_eq!(BLE_ADDRESS, frame.source);
+        assert_eq!(Some(-60), frame.rssi);
+        assert_eq!(std::vec![basic_id()], messages(frame.payload));
+
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn malformed_capture() {
+        assert_eq!(
+            Some(CaptureError::UnknownFormat(0x0102_0304)),
+            PcapReader::new(&[1, 2, 3, 4]).err()
+        );
+        assert_eq!(
+            Some(CaptureError::Malformed { offset: 0 }),
+            PcapReader::new(&PCAP[..20]).err()
+        );
+
+        // the last packet is cut short, the error ends the iteration
+        let mut reader = PcapReader::new(&PCAP[..PCAP.len() - 1]).unwrap();
+        assert_eq!(
+            Some(Err(CaptureError::Malformed { offset: 24 })),
+            reader.next().map(|r| r.map(|f| f.counter))
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn undecodable_frame() {
+        let mut capture = PCAP.to_vec();
+        // message type of the Basic ID message
+        let header = capture.len() - 3 - 25;
+        capture[header] = 0x72;
+
+        let mut reader = PcapReader::new(&capture).unwrap();
+        assert_eq!(
+            Some(Err(CaptureError::Decode(DecodeError::UnknownMessageType(
+                7
+            )))),
+            reader.next().map(|r| r.map(|f| f.counter))
+        );
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,298 @@
+use chrono::{DateTime, Utc};
+
+use crate::data::authentication::{
+    AuthPage, AuthPayload, AuthType, Authentication, MAX_PAGES, PAGE_DATA_SIZE, PAGE_ZERO_DATA_SIZE,
+};
+
+const BUFFER_SIZE: usize = PAGE_ZERO_DATA_SIZE + (MAX_PAGES - 1) * PAGE_DATA_SIZE;
+
+/// Collects authentication pages per UA until the full payload is available.
+///
+/// Pages are keyed by anything identifying the transmitter, e.g. its MAC address or UAS ID.
+/// At most `N` payloads are tracked at once, when all slots are in use the least recently
+/// updated one is dropped.
+///
+/// Transmitters repeat the pages of a payload. Once a payload is returned, its repeated pages
+/// are ignored and continuation pages are dropped until the page 0 of the next payload comes
+/// in, as they cannot be told apart from repeats of the previous one.
+/// ```
+/// # use remote_id::codec::auth::AuthReassembler;
+/// let mut reassembler = AuthReassembler::<[u8; 6], 4>::new();
+/// ```
+pub struct AuthReassembler<K, const N: usize> {
+    slots: [Option<Slot<K>>; N],
+    clock: u32,
+}
+
+struct Slot<K> {
+    key: K,
+    last_update: u32,
+    auth_type: AuthType,
+    header: Option<Header>,
+    /// Header of the last payload returned
+    completed: Option<Header>,
+    /// Bit `i` is set when page `i` has been received
+    received: u16,
+    buffer: [u8; BUFFER_SIZE],
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Header {
+    last_page_index: u8,
+    length: u8,
+    timestamp: DateTime<Utc>,
+}
+
+impl<K: PartialEq, const N: usize> AuthReassembler<K, N> {
+    pub fn new() -> Self {
+        Self {
+            slots: core::array::from_fn(|_| None),
+            clock: 0,
+        }
+    }
+
+    /// Add a page received from `key`, returns the payload once all of its pages are in.
+    pub fn push(&mut self, key: K, auth: &Authentication) -> Option<AuthPayload> {
+        self.clock = self.clock.wrapping_add(1);
+        let clock = self.clock;
+
+        let slot = self.slot_for(key, auth.auth_type);
+        slot.last_update = clock;
+
+        match &auth.page {
+            AuthPage::First {
+                last_page_index,
+                length,
+                timestamp,
+                data,
+            } => {
+                let header = Header {
+                    last_page_index: *last_page_index,
+                    length: *length,
+                    timestamp: *timestamp,
+                };
+
+                let capacity =
+                    PAGE_ZERO_DATA_SIZE + header.last_page_index as usize * PAGE_DATA_SIZE;
+                if header.last_page_index as usize >= MAX_PAGES || header.length as usize > capacity
+                {
+                    // malformed, the pages could never be assembled
+                    return None;
+                }
+
+                if slot.completed == Some(header) {
+                    // repeated page of the payload already returned
+                    return None;
+                }
+
+                if slot.header.is_some_and(|h| h != header) {
+                    // a new payload has started, drop what we got from the previous one
+                    slot.received = 0;
+                }
+
+                slot.header = Some(header);
+                slot.buffer[..PAGE_ZERO_DATA_SIZE].copy_from_slice(data);
+                slot.received |= 1;
+            }
+            AuthPage::Continuation { page_number, data } => {
+                let page_number = *page_number as usize;
+                if page_number == 0 || page_number >= MAX_PAGES {
+                    return None;
+                }
+                if slot.header.is_none() && slot.completed.is_some() {
+                    // a repeat of the payload already returned, or a page of the next one
+                    return None;
+                }
+
+                let start = PAGE_ZERO_DATA_SIZE + (page_number - 1) * PAGE_DATA_SIZE;
+                slot.buffer[start..(start + PAGE_DATA_SIZE)].copy_from_slice(data);
+                slot.received |= 1 << page_number;
+            }
+        }
+
+        let header = slot.header?;
+        let all_pages = ((1u32 << (header.last_page_index + 1)) - 1) as u16;
+        if slot.received & all_pages != all_pages {
+            return None;
+        }
+
+        let payload = AuthPayload::new(
+            slot.auth_type,
+            header.timestamp,
+            &slot.buffer[..header.length as usize],
+        );
+
+        slot.completed = slot.header.take();
+        slot.received = 0;
+
+        payload
+    }
+
+    /// Forget any partially received payload from `key`
+    pub fn clear(&mut self, key: &K) {
+        for slot in self.slots.iter_mut() {
+            if slot.as_ref().is_some_and(|s| s.key == *key) {
+                *slot = None;
+            }
+        }
+    }
+
+    fn slot_for(&mut self, key: K, auth_type: AuthType) -> &mut Slot<K> {
+        let index = self
+            .slots
+            .iter()
+            .position(|s| s.as_ref().is_some_and(|s| s.key == key))
+            .or_else(|| self.slots.iter().position(Option::is_none))
+            .unwrap_or_else(|| {
+                self.slots
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, s)| {
+                        s.as_ref()
+                            .map_or(0, |s| self.clock.wrapping_sub(s.last_update))
+                    })
+                    .map_or(0, |(i, _)| i)
+            });
+
+        let slot = &mut self.slots[index];
+        match slot {
+            Some(s) if s.key == key && s.auth_type == auth_type => {}
+            _ => {
+                *slot = Some(Slot {
+                    key,
+                    last_update: 0,
+                    auth_type,
+                    header: None,
+                    completed: None,
+                    received: 0,
+                    buffer: [0u8; BUFFER_SIZE],
+                });
+            }
+        }
+
+        slot.as_mut().unwrap()
+    }
+}
+
+impl<K: PartialEq, const N: usize> Default for AuthReassembler<K, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::codec::decode::from_message_buffer;
+    use crate::codec::encode::{authentication_pages, encode_authentication};
+    use crate::data::RemoteIDMessage;
+
+    fn payload(len: usize) -> AuthPayload {
+        let data: [u8; 255] = core::array::from_fn(|i| i as u8);
+        let timestamp = DateTime::parse_from_rfc3339("2024-07-04T14:05:54Z")
+            .unwrap()
+            .to_utc();
+        AuthPayload::new(AuthType::UasIdSignature, timestamp, &data[..len]).unwrap()
+    }
+
+    #[test]
+    fn reassemble_single_page() {
+        let payload = payload(10);
+        let mut reassembler = AuthReassembler::<u8, 2>::new();
+
+        let mut pages = authentication_pages(&payload);
+        let page = pages.next().unwrap();
+        assert!(pages.next().is_none());
+        assert_eq!(Some(payload.clone()), reassembler.push(1, &page));
+    }
+
+    #[test]
+    fn reassemble_out_of_order_through_codec() {
+        let payload = payload(255);
+        let mut buf = [0u8; 25 * 16];
+        let len = encode_authentication(&mut buf, &payload).unwrap();
+        assert_eq!(12 * 25, len);
+
+        let mut reassembler = AuthReassembler::<u8, 2>::new();
+        let mut result = None;
+        for page in buf[..len].chunks(25).rev() {
+            let Ok(RemoteIDMessage::Authentication(auth)) = from_message_buffer(page) else {
+                panic!("not an auth page");
+            };
+            assert!(result.is_none());
+            result = reassembler.push(7, &auth);
+        }
+
+        assert_eq!(Some(payload), result);
+    }
+
+    #[test]
+    fn reassemble_interleaved_transmitters() {
+        let a = payload(50);
+        let b = payload(60);
+        let mut reassembler = AuthReassembler::<u8, 2>::new();
+
+        let mut pages_a = authentication_pages(&a);
+        let mut pages_b = authentication_pages(&b);
+
+        assert_eq!(None, reassembler.push(1, &pages_a.next().unwrap()));
+        assert_eq!(None, reassembler.push(2, &pages_b.next().unwrap()));
+        assert_eq!(None, reassembler.push(1, &pages_a.next().unwrap()));
+        assert_eq!(None, reassembler.push(2, &pages_b.next().unwrap()));
+        assert_eq!(
+            Some(a.clone()),
+            reassembler.push(1, &pages_a.next().unwrap())
+        );
+        assert_eq!(
+            Some(b.clone()),
+            reassembler.push(2, &pages_b.next().unwrap())
+        );
+    }
+
+    #[test]
+    fn reassemble_after_repeated_pages() {
+        let a = payload(50);
+        let timestamp = a.timestamp + chrono::Duration::seconds(1);
+        let b = AuthPayload::new(AuthType::UasIdSignature, timestamp, &[0xB0; 50]).unwrap();
+        let mut reassembler = AuthReassembler::<u8, 1>::new();
+
+        let result = authentication_pages(&a).find_map(|page| reassembler.push(1, &page));
+        assert_eq!(Some(a.clone()), result);
+
+        // the transmitter repeats the payload, it is not returned again
+        for page in authentication_pages(&a).skip(1) {
+            assert_eq!(None, reassembler.push(1, &page));
+        }
+        assert_eq!(
+            None,
+            reassembler.push(1, &authentication_pages(&a).next().unwrap())
+        );
+
+        let mut pages_b = authentication_pages(&b);
+        assert_eq!(None, reassembler.push(1, &pages_b.next().unwrap()));
+        assert_eq!(None, reassembler.push(1, &pages_b.next().unwrap()));
+        assert_eq!(
+            Some(b.clone()),
+            reassembler.push(1, &pages_b.next().unwrap())
+        );
+    }
+
+    #[test]
+    fn reject_inconsistent_length() {
+        let mut reassembler = AuthReassembler::<u8, 1>::new();
+        let auth = Authentication {
+            auth_type: AuthType::UasIdSignature,
+            page: AuthPage::First {
+                last_page_index: 0,
+                length: 18,
+                timestamp: DateTime::from_timestamp(1546300800, 0).unwrap(),
+                data: [0; PAGE_ZERO_DATA_SIZE],
+            },
+        };
+        assert_eq!(None, reassembler.push(1, &auth));
+    }
+}
@@ -1,24 +1,114 @@
 use core::time::Duration;
 
-use basic_id::{BasicId, IdType, UAType};
+use authentication::{
+    AuthPage, AuthType, Authentication, MAX_PAGES, PAGE_DATA_SIZE, PAGE_ZERO_DATA_SIZE,
+};
+use basic_id::{BasicId, IdType, SerialNumber, UAType, UasId, UasIdError};
 use chrono::DateTime;
+use coordinate::Coordinate;
 use location::{
     HeightType, HorizontalAccuracy, Location, OperationalStatus, SpeedAccuracy, VerticalAccuracy,
 };
 use operator_id::{OperatorId, OperatorIdType};
+use self_id::{Description, SelfId, DESCRIPTION_SIZE};
 use system::{ClassificationType, OperatorLocationType, System};
 
 use crate::data::system::{UaCategory, UaClass, UaClassification};
 use crate::{data::*, get_bits, get_bytes};
 use crate::{MAX_ID_BYTE_SIZE, OPEN_DRONE_ID_AD_CODE};
 
-use super::{copy_to_id, MessageType};
+use super::{copy_to_id, MessageKind, MAX_MESSAGES_IN_PACK, MESSAGE_SIZE, TIMESTAMP_EPOCH_2019};
+
+/// Reasons a buffer could not be decoded into a message
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DecodeError {
+    /// The buffer holds fewer bytes than the message requires
+    BufferTooShort { expected: usize, actual: usize },
+    /// Service data did not start with the Open Drone ID application code
+    WrongAppCode(u8),
+    /// The message type nibble does not name a known message
+    UnknownMessageType(u8),
+    /// A Message Pack was found where a single message was expected
+    UnexpectedMessagePack,
+    /// The protocol version nibble is reserved
+    UnsupportedProtocolVersion(u8),
+    /// A field holds a value the specification does not allow
+    FieldOutOfRange { field: &'static str, value: u32 },
+    /// A timestamp could not be represented
+    InvalidTimestamp,
+    /// The UAS ID of a Basic ID message is not valid for its ID type, only checked in
+    /// [`DecodeMode::Strict`]
+    InvalidUasId(UasIdError),
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::BufferTooShort { expected, actual } => {
+                write!(
+                    f,
+                    "buffer too short: expected {expected} bytes, got {actual}"
+                )
+            }
+            DecodeError::WrongAppCode(code) => write!(f, "wrong app code: {code:#04x}"),
+            DecodeError::UnknownMessageType(t) => write!(f, "unknown message type: {t:#x}"),
+            DecodeError::UnexpectedMessagePack => write!(f, "unexpected message pack"),
+            DecodeError::UnsupportedProtocolVersion(v) => {
+                write!(f, "unsupported protocol version: {v}")
+            }
+            DecodeError::FieldOutOfRange { field, value } => {
+                write!(f, "{field} out of range: {value}")
+            }
+            DecodeError::InvalidTimestamp => write!(f, "invalid timestamp"),
+            DecodeError::InvalidUasId(e) => write!(f, "invalid UAS ID: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// How thoroughly decoded messages are checked
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum DecodeMode {
+    /// Accept any field value the message format can carry
+    #[default]
+    Lenient,
+    /// Additionally reject a Basic ID message with a UAS ID that is not valid for its ID type
+    /// with [`DecodeError::InvalidUasId`]. Serial numbers must follow ANSI/CTA-2063-A.
+    Strict,
+}
+
+/// Read the protocol version from the low nibble of a message header
+fn protocol_version(header: u8) -> Result<ProtocolVersion, DecodeError> {
+    // protocol version, reserved for private use
+    let version = get_bits!(header, 3..0);
+    ProtocolVersion::try_from(version).map_err(|_| DecodeError::UnsupportedProtocolVersion(version))
+}
+
+/// Read the message kind from the high nibble of a message header
+fn message_kind(header: u8) -> Result<MessageKind, DecodeError> {
+    let message_type = get_bits!(header, 7..4);
+    MessageKind::try_from(message_type).map_err(|_| DecodeError::UnknownMessageType(message_type))
+}
+
+fn ensure_len(data: &[u8], expected: usize) -> Result<(), DecodeError> {
+    if data.len() < expected {
+        return Err(DecodeError::BufferTooShort {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    Ok(())
+}
+
+pub fn from_service_data(data: &[u8]) -> Result<RemoteIDMessage, DecodeError> {
+    ensure_len(data, 2)?;
 
-pub fn from_service_data(data: &[u8]) -> Option<RemoteIDMessage> {
     let first_byte = data[0];
     if first_byte != OPEN_DRONE_ID_AD_CODE {
         // all RemoteID Messages start with this byte?
-        return None;
+        return Err(DecodeError::WrongAppCode(first_byte));
     }
 
     let _message_counter = data[1];
@@ -26,39 +116,279 @@ pub fn from_service_data(data: &[u8]) -> Option<RemoteIDMessage> {
     from_message_buffer(&data[2..])
 }
 
-pub fn from_message_buffer(data: &[u8]) -> Option<RemoteIDMessage> {
-    // protocol version, reserved for private use
-    let _version = get_bits!(data[0], 3..0);
+pub fn from_message_buffer(data: &[u8]) -> Result<RemoteIDMessage, DecodeError> {
+    decode_versioned(data).map(|(_, msg)| msg)
+}
+
+/// Decode a single message along with the protocol version it was sent in
+pub fn decode_versioned(data: &[u8]) -> Result<(ProtocolVersion, RemoteIDMessage), DecodeError> {
+    decode_with_mode(data, DecodeMode::Lenient)
+}
+
+/// Decode a single message along with the protocol version it was sent in, checked as
+/// thoroughly as `mode` asks for
+/// ```
+/// # use remote_id::codec::decode::{decode_with_mode, DecodeError, DecodeMode};
+/// # use remote_id::data::basic_id::{SerialNumberError, UasIdError};
+/// let mut message = [0u8; 25];
+/// // Basic ID, serial number
+/// message[1] = 0x10;
+/// message[2..10].copy_from_slice(b"1596F359");
+/// assert!(decode_with_mode(&message, DecodeMode::Lenient).is_ok());
+/// assert_eq!(
+///     Err(DecodeError::InvalidUasId(UasIdError::InvalidSerialNumber(
+///         SerialNumberError::LengthMismatch { expected: 15, actual: 3 }
+///     ))),
+///     decode_with_mode(&message, DecodeMode::Strict)
+/// );
+/// ```
+pub fn decode_with_mode(
+    data: &[u8],
+    mode: DecodeMode,
+) -> Result<(ProtocolVersion, RemoteIDMessage), DecodeError> {
+    ensure_len(data, MESSAGE_SIZE)?;
+
+    let version = protocol_version(data[0])?;
+
+    let msg = match message_kind(data[0])? {
+        MessageKind::BasicID => parse_basic_id(data, mode),
+        MessageKind::Location => parse_location(data, version),
+        MessageKind::OperatorId => parse_operator_id(data),
+        MessageKind::System => parse_system(data, version),
+        MessageKind::Authentication => parse_authentication(data),
+        MessageKind::SelfId => parse_self_id(data),
+
+        // a pack holds several messages, use `decode_message_pack` instead
+        MessageKind::MessagePack => Err(DecodeError::UnexpectedMessagePack),
+    }?;
+
+    Ok((version, msg))
+}
+
+/// Header of a message, Message Pack or service data, read without decoding the body
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MessageHeader {
+    pub kind: MessageKind,
+    pub version: ProtocolVersion,
+    /// Message counter, only carried by service data
+    pub counter: Option<u8>,
+}
+
+/// Read the header of a message, Message Pack or Bluetooth service data without decoding the
+/// body, e.g. to drop repeated messages early.
+///
+/// Service data starts with the app code, followed by the counter and the message. The app
+/// code would be a Basic ID message of a reserved protocol version, so it cannot be mistaken
+/// for a message header.
+/// ```
+/// # use remote_id::codec::MessageKind;
+/// # use remote_id::codec::decode::peek_header;
+/// let header = peek_header(&[0x0D, 42, 0x12]).unwrap();
+/// assert_eq!(MessageKind::Location, header.kind);
+/// assert_eq!(Some(42), header.counter);
+/// ```
+pub fn peek_header(data: &[u8]) -> Result<MessageHeader, DecodeError> {
+    ensure_len(data, 1)?;
+
+    let (counter, header) = if data[0] == OPEN_DRONE_ID_AD_CODE {
+        ensure_len(data, 3)?;
+        (Some(data[1]), data[2])
+    } else {
+        (None, data[0])
+    };
 
-    match MessageType::from(get_bits!(data[0], 7..4)) {
-        MessageType::BasicId => parse_basic_id(data),
-        MessageType::Location => parse_location(data),
-        MessageType::OperatorId => parse_operator_id(data),
-        MessageType::System => parse_system(data),
+    Ok(MessageHeader {
+        kind: message_kind(header)?,
+        version: protocol_version(header)?,
+        counter,
+    })
+}
+
+/// Iterator over the messages of a Message Pack, decoding each one on the fly
+#[derive(Debug, Clone)]
+pub struct MessagePack<'a> {
+    version: ProtocolVersion,
+    mode: DecodeMode,
+    messages: core::slice::ChunksExact<'a, u8>,
+}
+
+impl MessagePack<'_> {
+    /// Protocol version of the pack header, each message carries its own as well
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
 
-        // we have no examples for these yet
-        MessageType::Selfid => todo!(),
-        MessageType::Auth => todo!(),
-        MessageType::MessagePack => todo!(),
+    /// Number of messages in the pack that have not been yielded yet
+    pub fn remaining(&self) -> usize {
+        self.messages.len()
+    }
 
-        MessageType::Invalid => None,
+    /// Check the contained messages as thoroughly as `mode` asks for, lenient by default
+    pub fn with_mode(self, mode: DecodeMode) -> Self {
+        Self { mode, ..self }
     }
 }
 
-fn parse_basic_id(buffer: &[u8]) -> Option<RemoteIDMessage> {
+impl Iterator for MessagePack<'_> {
+    type Item = Result<RemoteIDMessage, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // nested packs are rejected by `decode_with_mode`
+        let mode = self.mode;
+        self.messages
+            .next()
+            .map(|message| decode_with_mode(message, mode).map(|(_, message)| message))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.messages.size_hint()
+    }
+}
+
+impl ExactSizeIterator for MessagePack<'_> {}
+
+/// Decode a Message Pack (type 0xF), yielding each contained message
+/// ```
+/// # use remote_id::codec::decode::decode_message_pack;
+/// # use remote_id::codec::encode::{encode_message_pack, EncodeMode};
+/// let mut buf = [0u8; 3];
+/// let len = encode_message_pack(&mut buf, &[], EncodeMode::Strict).unwrap();
+/// assert_eq!(0, decode_message_pack(&buf[..len]).unwrap().count());
+/// ```
+pub fn decode_message_pack(data: &[u8]) -> Result<MessagePack<'_>, DecodeError> {
+    ensure_len(data, 3)?;
+
+    if message_kind(data[0])? != MessageKind::MessagePack {
+        return Err(DecodeError::UnknownMessageType(get_bits!(data[0], 7..4)));
+    }
+
+    let version = protocol_version(data[0])?;
+
+    // Single Message Size, always 25 bytes
+    let message_size = data[1];
+    if message_size as usize != MESSAGE_SIZE {
+        return Err(DecodeError::FieldOutOfRange {
+            field: "message size",
+            value: message_size as u32,
+        });
+    }
+
+    // No. of messages in pack
+    let count = data[2];
+    if count as usize > MAX_MESSAGES_IN_PACK {
+        return Err(DecodeError::FieldOutOfRange {
+            field: "message count",
+            value: count as u32,
+        });
+    }
+
+    let end = 3 + count as usize * MESSAGE_SIZE;
+    ensure_len(data, end)?;
+
+    Ok(MessagePack {
+        version,
+        mode: DecodeMode::Lenient,
+        messages: data[3..end].chunks_exact(MESSAGE_SIZE),
+    })
+}
+
+fn parse_basic_id(buffer: &[u8], mode: DecodeMode) -> Result<RemoteIDMessage, DecodeError> {
     let id_type = IdType::from(get_bits!(buffer[1], 7..4));
     let ua_type = UAType::from(get_bits!(buffer[1], 3..0));
 
-    let uas_id = copy_to_id(&get_bytes!(buffer, 2, crate::MAX_ID_BYTE_SIZE));
+    let uas_id = copy_to_id(get_bytes!(buffer, 2, crate::MAX_ID_BYTE_SIZE));
 
-    Some(RemoteIDMessage::BasicID(BasicId {
+    let basic_id = BasicId {
         id_type,
         ua_type,
         uas_id,
+    };
+
+    if mode == DecodeMode::Strict {
+        let check = |uas_id| match uas_id {
+            UasId::SerialNumber(text) => SerialNumber::try_from(&text)
+                .map(|_| ())
+                .map_err(UasIdError::from),
+            _ => Ok(()),
+        };
+        basic_id
+            .typed_uas_id()
+            .and_then(check)
+            .map_err(DecodeError::InvalidUasId)?;
+    }
+
+    Ok(RemoteIDMessage::BasicID(basic_id))
+}
+
+fn parse_authentication(buffer: &[u8]) -> Result<RemoteIDMessage, DecodeError> {
+    // Authentication Type: Bits [7..4]
+    let auth_type = AuthType::from(get_bits!(buffer[1], 7..4));
+    // Page Number: Bits [3..0]
+    let page_number = get_bits!(buffer[1], 3..0);
+
+    let page = if page_number == 0 {
+        // Last Page Index
+        let last_page_index = get_bytes!(buffer, 2, 1);
+        if last_page_index as usize >= MAX_PAGES {
+            return Err(DecodeError::FieldOutOfRange {
+                field: "last page index",
+                value: last_page_index as u32,
+            });
+        }
+
+        // Length
+        //   Total bytes of authentication data
+        let length = get_bytes!(buffer, 3, 1);
+        let capacity = PAGE_ZERO_DATA_SIZE + last_page_index as usize * PAGE_DATA_SIZE;
+        if length as usize > capacity {
+            return Err(DecodeError::FieldOutOfRange {
+                field: "authentication length",
+                value: length as u32,
+            });
+        }
+
+        // Timestamp
+        //   Seconds since 00:00:00 01/01/2019
+        let secs = u32::from_le_bytes(get_bytes!(buffer, 4, 4));
+        let timestamp = DateTime::from_timestamp(secs as i64 + TIMESTAMP_EPOCH_2019, 0)
+            .ok_or(DecodeError::InvalidTimestamp)?;
+
+        let mut data = [0u8; PAGE_ZERO_DATA_SIZE];
+        data.copy_from_slice(get_bytes!(buffer, 8, PAGE_ZERO_DATA_SIZE));
+
+        AuthPage::First {
+            last_page_index,
+            length,
+            timestamp,
+            data,
+        }
+    } else {
+        let mut data = [0u8; PAGE_DATA_SIZE];
+        data.copy_from_slice(get_bytes!(buffer, 2, PAGE_DATA_SIZE));
+
+        AuthPage::Continuation { page_number, data }
+    };
+
+    Ok(RemoteIDMessage::Authentication(Authentication {
+        auth_type,
+        page,
     }))
 }
 
-fn parse_operator_id(buffer: &[u8]) -> Option<RemoteIDMessage> {
+fn parse_self_id(buffer: &[u8]) -> Result<RemoteIDMessage, DecodeError> {
+    // Description Type
+    let description_type = get_bytes!(buffer, 1, 1);
+
+    // Description
+    let mut text = [0u8; DESCRIPTION_SIZE];
+    text.copy_from_slice(get_bytes!(buffer, 2, DESCRIPTION_SIZE));
+
+    Ok(RemoteIDMessage::SelfId(SelfId {
+        description: Description::new(description_type, text),
+    }))
+}
+
+fn parse_operator_id(buffer: &[u8]) -> Result<RemoteIDMessage, DecodeError> {
     // Operator ID Type
     let id_type = OperatorIdType::from(buffer[1]);
 
@@ -67,28 +397,35 @@ fn parse_operator_id(buffer: &[u8]) -> Option<RemoteIDMessage> {
 
     let _reserved = get_bytes!(buffer, MAX_ID_BYTE_SIZE + 2, 3);
 
-    Some(RemoteIDMessage::OperatorId(OperatorId {
+    Ok(RemoteIDMessage::OperatorId(OperatorId {
         id_type,
         operator_id,
     }))
 }
 
-fn parse_system(buffer: &[u8]) -> Option<RemoteIDMessage> {
+fn parse_system(buffer: &[u8], version: ProtocolVersion) -> Result<RemoteIDMessage, DecodeError> {
+    // F3411-19 ends the message after the area floor, bytes 17..25 are reserved
+    let v19 = version == ProtocolVersion::F3411_19;
+
     let flags = buffer[1];
     // Reserved: Bits [7..5]
     let _reserved = get_bits!(flags, 7..5);
-    // Classification Type: Bits [4..2]
-    let classification_type = ClassificationType::from(get_bits!(flags, 4..2));
+    // Classification Type: Bits [4..2], reserved in F3411-19
+    let classification_type = if v19 {
+        ClassificationType::Undeclared
+    } else {
+        ClassificationType::from(get_bits!(flags, 4..2))
+    };
     // Operator Location/Altitude source type: Bits [1–0]
     let operator_location_type = OperatorLocationType::from(get_bits!(flags, 1..0));
 
     // Operator Latitude
     //    Latitude of Remote Pilot
-    let operator_latidute = u32::from_le_bytes(get_bytes!(buffer, 2, 4)) as f32 / 10000000.;
+    let operator_latidute = Coordinate::from_raw(i32::from_le_bytes(get_bytes!(buffer, 2, 4)));
 
     // Operator Longitude
     //   Longitude of Remote Pilot
-    let operator_longitude = u32::from_le_bytes(get_bytes!(buffer, 6, 4)) as f32 / 10000000.;
+    let operator_longitude = Coordinate::from_raw(i32::from_le_bytes(get_bytes!(buffer, 6, 4)));
 
     // Area Count
     //   Number of aircraft in Area, group or formation (default 1)
@@ -105,29 +442,40 @@ fn parse_system(buffer: &[u8]) -> Option<RemoteIDMessage> {
 
     // Area Floor
     //   Group operations floor WGS-84 HAE (Altitude + 1000 m)/0.5
-    let area_floor = u16::from_be_bytes(get_bytes!(buffer, 15, 2)) as f32 / 2. - 1000.;
+    let area_floor = u16::from_le_bytes(get_bytes!(buffer, 15, 2)) as f32 / 2. - 1000.;
 
-    // TODO UA Classification
-    let ua_classification = if classification_type == ClassificationType::EuropeanUnion {
+    // UA Classification
+    //   Category: Bits [7..4], Class: Bits [3..0]
+    let ua_classification = if classification_type != ClassificationType::Undeclared {
         UaClassification {
-            category: UaCategory::from(get_bits!(buffer[16], 4..7)),
-            class: UaClass::from(get_bits!(buffer[16], 0..3)),
+            category: UaCategory::from(get_bits!(buffer[17], 7..4)),
+            class: UaClass::from(get_bits!(buffer[17], 3..0)),
         }
     } else {
         UaClassification::undefined()
     };
 
     // Operator Altitude
-    let operator_altitude = u16::from_le_bytes(get_bytes!(buffer, 18, 2)) as f32 / 2. - 1000.;
+    let operator_altitude = if v19 {
+        -1000.
+    } else {
+        u16::from_le_bytes(get_bytes!(buffer, 18, 2)) as f32 / 2. - 1000.
+    };
 
     // Timestamp
-    let unix_secs = u32::from_le_bytes(get_bytes!(buffer, 20, 4));
-    let timestamp = DateTime::from_timestamp(unix_secs as i64 + 1546300800, 0)?;
+    let timestamp = if v19 {
+        None
+    } else {
+        let unix_secs = u32::from_le_bytes(get_bytes!(buffer, 20, 4));
+        let timestamp = DateTime::from_timestamp(unix_secs as i64 + TIMESTAMP_EPOCH_2019, 0)
+            .ok_or(DecodeError::InvalidTimestamp)?;
+        Some(timestamp)
+    };
 
     // Reserved
     let _reserved = get_bytes!(buffer, 24, 1);
 
-    Some(RemoteIDMessage::System(System {
+    Ok(RemoteIDMessage::System(System {
         classification_type,
         operator_location_type,
         operator_latidute,
@@ -142,7 +490,13 @@ fn parse_system(buffer: &[u8]) -> Option<RemoteIDMessage> {
     }))
 }
 
-fn parse_location(buffer: &[u8]) -> Option<RemoteIDMessage> {
+/// Decode an altitude sent as (altitude + 1000 m) / 0.5, -1000 m is "unknown"
+fn parse_altitude(bytes: [u8; 2]) -> Option<f32> {
+    let raw = u16::from_le_bytes(bytes);
+    (raw != 0).then_some(raw as f32 / 2.0 - 1000.)
+}
+
+fn parse_location(buffer: &[u8], version: ProtocolVersion) -> Result<RemoteIDMessage, DecodeError> {
     // Status, Flags
     let status_flags = get_bytes!(buffer, 1, 1);
 
@@ -153,39 +507,52 @@ fn parse_location(buffer: &[u8]) -> Option<RemoteIDMessage> {
     let speed_multiplier = get_bits!(status_flags, 0..0);
 
     // Track Direction
-    let track_direction = get_bytes!(buffer, 2, 1);
+    let track_direction = get_bytes!(buffer, 2, 1) as u16;
     let track_direction = if ew_direction_segment > 0 {
-        track_direction as u16 + 180
+        track_direction + 180
     } else {
-        ew_direction_segment as u16
+        track_direction
     };
+    // 361 is "unknown"
+    let track_direction = (track_direction != 361).then_some(track_direction);
 
     // Speed
     let speed = get_bytes!(buffer, 3, 1);
     let speed = if speed_multiplier == 0 {
-        speed as f32 * 0.25
+        Some(speed as f32 * 0.25)
+    } else if speed == u8::MAX {
+        // 255 m/s is "unknown"
+        None
     } else {
-        (speed as f32 + 255. * 0.25) * 0.75
+        Some(speed as f32 * 0.75 + 255. * 0.25)
     };
 
     // Vertical Speed
-    let vertical_speed = get_bytes!(buffer, 4, 1);
-    let vertical_speed = (vertical_speed as f32 * 0.5) as f32;
+    //   Signed, 63 m/s is "unknown"
+    let vertical_speed = get_bytes!(buffer, 4, 1) as i8;
+    let vertical_speed = (vertical_speed != 126).then_some(vertical_speed as f32 * 0.5);
 
     // Latitude
-    let latidute = u32::from_le_bytes(get_bytes!(buffer, 5, 4)) as f32 / 10000000.;
+    let latidute = Coordinate::from_raw(i32::from_le_bytes(get_bytes!(buffer, 5, 4)));
 
     // Longitude
-    let longitude = u32::from_le_bytes(get_bytes!(buffer, 9, 4)) as f32 / 10000000.;
+    let longitude = Coordinate::from_raw(i32::from_le_bytes(get_bytes!(buffer, 9, 4)));
+
+    // 0 for both is "unknown"
+    let (latidute, longitude) = if latidute.raw() == 0 && longitude.raw() == 0 {
+        (None, None)
+    } else {
+        (Some(latidute), Some(longitude))
+    };
 
     // Pressure Altitude
-    let pressure_altitude = u16::from_le_bytes(get_bytes!(buffer, 13, 2)) as f32 / 2.0 - 1000.;
+    let pressure_altitude = parse_altitude(get_bytes!(buffer, 13, 2));
 
     // Geodetic Altitude
-    let geodetic_altitude = u16::from_le_bytes(get_bytes!(buffer, 15, 2)) as f32 / 2.0 - 1000.;
+    let geodetic_altitude = parse_altitude(get_bytes!(buffer, 15, 2));
 
-    // Geodetic Altitude
-    let height = u16::from_le_bytes(get_bytes!(buffer, 17, 2)) as f32 / 2.0 - 1000.;
+    // Height
+    let height = parse_altitude(get_bytes!(buffer, 17, 2));
 
     // Vertical / Horizontal Accuracy
     let accuracy = get_bytes!(buffer, 19, 1);
@@ -196,16 +563,24 @@ fn parse_location(buffer: &[u8]) -> Option<RemoteIDMessage> {
     let baro_altitude_accuracy = VerticalAccuracy::from(get_bits!(accuracy, 7..4));
     let speed_accuracy = SpeedAccuracy::from(get_bits!(accuracy, 3..0));
 
-    let timestamp = u16::from_le_bytes(get_bytes!(buffer, 21, 2)) as f32 / 10.;
+    // Timestamp
+    //   1/10ths of seconds since the last hour relative to UTC time
+    let timestamp = u16::from_le_bytes(get_bytes!(buffer, 21, 2));
+    if timestamp > 36000 && timestamp != u16::MAX {
+        return Err(DecodeError::InvalidTimestamp);
+    }
+    // 0xFFFF is "unknown"
+    let timestamp = (timestamp != u16::MAX).then_some(timestamp as f32 / 10.);
 
+    // Reserved / Timestamp Accuracy, the whole byte is reserved in F3411-19
     let timestamp_accuracy = get_bits!(get_bytes!(buffer, 23, 1), 3..0);
-    let timestamp_accuracy = if timestamp_accuracy == 0 {
+    let timestamp_accuracy = if timestamp_accuracy == 0 || version == ProtocolVersion::F3411_19 {
         None
     } else {
         Some(Duration::from_secs_f32(timestamp_accuracy as f32 * 0.1))
     };
 
-    Some(RemoteIDMessage::Location(Location {
+    Ok(RemoteIDMessage::Location(Location {
         height_type,
         operational_status,
         latidute,
@@ -272,19 +647,19 @@ mod test {
         let expected = RemoteIDMessage::Location(Location {
             height_type: HeightType::AboveTakeoff,
             operational_status: OperationalStatus::Airborne,
-            speed: 0.,
-            pressure_altitude: 190.5,
-            geodetic_altitude: 210.0,
-            vertical_speed: 0.,
-            latidute: 49.874855,
-            longitude: 8.912173,
-            height: 0.,
-            track_direction: 337,
+            speed: Some(0.),
+            pressure_altitude: Some(190.5),
+            geodetic_altitude: Some(210.0),
+            vertical_speed: Some(0.),
+            latidute: Some(Coordinate::from_raw(498748559)),
+            longitude: Some(Coordinate::from_raw(89121728)),
+            height: Some(0.),
+            track_direction: Some(337),
             horizontal_accuracy: location::HorizontalAccuracy::LessThan_3_m,
             vertical_accuracy: location::VerticalAccuracy::LessThan_3_m,
             baro_altitude_accuracy: location::VerticalAccuracy::Unknown,
             speed_accuracy: location::SpeedAccuracy::LessThan_third_mps,
-            timestamp: 361.0,
+            timestamp: Some(361.0),
             timestamp_accuracy: None,
         });
 
@@ -300,19 +675,19 @@ mod test {
         let expected = RemoteIDMessage::Location(Location {
             height_type: HeightType::AboveTakeoff,
             operational_status: OperationalStatus::Airborne,
-            speed: 5.25,
-            pressure_altitude: 201.5,
-            geodetic_altitude: 218.0,
-            vertical_speed: 0.,
-            latidute: 49.875015,
-            longitude: 8.912442,
-            height: 11.0,
-            track_direction: 0,
+            speed: Some(5.25),
+            pressure_altitude: Some(201.5),
+            geodetic_altitude: Some(218.0),
+            vertical_speed: Some(0.),
+            latidute: Some(Coordinate::from_raw(498750140)),
+            longitude: Some(Coordinate::from_raw(89124421)),
+            height: Some(11.0),
+            track_direction: Some(52),
             horizontal_accuracy: location::HorizontalAccuracy::LessThan_3_m,
             vertical_accuracy: location::VerticalAccuracy::LessThan_3_m,
             baro_altitude_accuracy: location::VerticalAccuracy::Unknown,
             speed_accuracy: location::SpeedAccuracy::LessThan_third_mps,
-            timestamp: 886.0,
+            timestamp: Some(886.0),
             timestamp_accuracy: None,
         });
 
@@ -332,6 +707,127 @@ mod test {
         std::dbg!(from_service_data(&service_data).unwrap());
     }
 
+    #[test]
+    fn decode_location_descending() {
+        let mut buffer = [0u8; 25];
+        buffer[0] = 0x12;
+        // EW segment and speed multiplier set
+        buffer[1] = 0b0010_0011;
+        buffer[2] = 90;
+        buffer[3] = 48;
+        buffer[4] = -7i8 as u8;
+
+        let Ok(RemoteIDMessage::Location(location)) = from_message_buffer(&buffer) else {
+            panic!("expected a Location message");
+        };
+        assert_eq!(Some(270), location.track_direction);
+        assert_eq!(Some(99.75), location.speed);
+        assert_eq!(Some(-3.5), location.vertical_speed);
+    }
+
+    #[test]
+    fn decode_authentication_page_0() {
+        let expected = RemoteIDMessage::Authentication(Authentication {
+            auth_type: AuthType::UasIdSignature,
+            page: AuthPage::First {
+                last_page_index: 1,
+                length: 30,
+                timestamp: DateTime::parse_from_rfc3339("2024-07-04T14:05:54Z")
+                    .unwrap()
+                    .to_utc(),
+                data: [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17],
+            },
+        });
+
+        let service_data = [
+            13, 3, 34, 16, 1, 30, 194, 254, 91, 10, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14,
+            15, 16, 17,
+        ];
+        assert_eq!(expected, from_service_data(&service_data).unwrap());
+    }
+
+    #[test]
+    fn decode_authentication_page_1() {
+        let expected = RemoteIDMessage::Authentication(Authentication {
+            auth_type: AuthType::UasIdSignature,
+            page: AuthPage::Continuation {
+                page_number: 1,
+                data: [
+                    18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0,
+                ],
+            },
+        });
+
+        let service_data = [
+            13, 4, 34, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0,
+        ];
+        assert_eq!(expected, from_service_data(&service_data).unwrap());
+    }
+
+    #[test]
+    fn decode_self_id() {
+        let service_data = [
+            13, 5, 50, 0, 68, 114, 111, 110, 101, 32, 115, 117, 114, 118, 101, 121, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0,
+        ];
+
+        let Ok(RemoteIDMessage::SelfId(self_id)) = from_service_data(&service_data) else {
+            panic!("expected a Self-ID message");
+        };
+        assert!(matches!(self_id.description, Description::Text(_)));
+        assert_eq!(Some("Drone survey"), self_id.text());
+    }
+
+    #[test]
+    fn decode_self_id_description_types() {
+        let mut buffer = [0u8; 25];
+        buffer[0] = 0x32;
+        buffer[2..5].copy_from_slice("SOS".as_bytes());
+
+        for (description_type, expected) in [
+            (1, Description::Emergency(copy_to_description("SOS"))),
+            (2, Description::ExtendedStatus(copy_to_description("SOS"))),
+            (3, Description::Reserved(3, copy_to_description("SOS"))),
+            (200, Description::Reserved(200, copy_to_description("SOS"))),
+            (
+                201,
+                Description::PrivateUse(201, copy_to_description("SOS")),
+            ),
+            (
+                255,
+                Description::PrivateUse(255, copy_to_description("SOS")),
+            ),
+        ] {
+            buffer[1] = description_type;
+            assert_eq!(
+                Ok(RemoteIDMessage::SelfId(SelfId {
+                    description: expected
+                })),
+                from_message_buffer(&buffer)
+            );
+        }
+    }
+
+    #[test]
+    fn decode_self_id_not_ascii() {
+        let mut buffer = [0u8; 25];
+        buffer[0] = 0x32;
+        buffer[2..5].copy_from_slice(&[0x41, 0xC3, 0xA4]);
+
+        let Ok(RemoteIDMessage::SelfId(self_id)) = from_message_buffer(&buffer) else {
+            panic!("expected a Self-ID message");
+        };
+        assert_eq!(None, self_id.text());
+    }
+
+    fn copy_to_description(text: &str) -> [u8; DESCRIPTION_SIZE] {
+        let mut buffer = [0u8; DESCRIPTION_SIZE];
+        buffer[..text.len()].copy_from_slice(text.as_bytes());
+        buffer
+    }
+
     #[test]
     fn decode_operator_id() {
         let expected = RemoteIDMessage::OperatorId(OperatorId {
@@ -364,8 +860,8 @@ mod test {
         let expected = RemoteIDMessage::System(System {
             classification_type: ClassificationType::EuropeanUnion,
             operator_location_type: OperatorLocationType::TakeOff,
-            operator_latidute: 49.874855,
-            operator_longitude: 8.912173,
+            operator_latidute: Coordinate::from_raw(498748547),
+            operator_longitude: Coordinate::from_raw(89121724),
             operator_altitude: 210.,
             area_ceiling: -1000.,
             area_count: 1,
@@ -375,9 +871,11 @@ mod test {
                 category: UaCategory::Open,
                 class: UaClass::Undefined,
             },
-            timestamp: DateTime::parse_from_rfc3339(&"2024-07-04T14:05:54Z")
-                .unwrap()
-                .to_utc(),
+            timestamp: Some(
+                DateTime::parse_from_rfc3339("2024-07-04T14:05:54Z")
+                    .unwrap()
+                    .to_utc(),
+            ),
         });
 
         let service_data = [
@@ -386,4 +884,261 @@ mod test {
         ];
         assert_eq!(expected, from_service_data(&service_data).unwrap());
     }
+
+    #[test]
+    fn decode_message_pack_1() {
+        let basic_id = [
+            2, 16, 49, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 48, 57,
+            0, 0, 0,
+        ];
+        let operator_id = [
+            82, 0, 78, 85, 76, 76, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+
+        let mut pack = [0u8; 3 + 2 * 25];
+        pack[..3].copy_from_slice(&[0xF2, 25, 2]);
+        pack[3..28].copy_from_slice(&basic_id);
+        pack[28..53].copy_from_slice(&operator_id);
+
+        let mut messages = decode_message_pack(&pack).unwrap();
+        assert_eq!(2, messages.len());
+        assert_eq!(from_message_buffer(&basic_id), messages.next().unwrap());
+        assert_eq!(from_message_buffer(&operator_id), messages.next().unwrap());
+        assert_eq!(None, messages.next());
+    }
+
+    #[test]
+    fn decode_message_pack_invalid() {
+        assert_eq!(
+            Err(DecodeError::UnknownMessageType(0)),
+            decode_message_pack(&[0x02, 25, 0]).map(|_| ())
+        );
+        assert_eq!(
+            Err(DecodeError::FieldOutOfRange {
+                field: "message size",
+                value: 24
+            }),
+            decode_message_pack(&[0xF2, 24, 0]).map(|_| ())
+        );
+        assert_eq!(
+            Err(DecodeError::FieldOutOfRange {
+                field: "message count",
+                value: 10
+            }),
+            decode_message_pack(&[[0xF2, 25, 10].as_slice(), &[0; 250]].concat()).map(|_| ())
+        );
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 28,
+                actual: 5
+            }),
+            decode_message_pack(&[0xF2, 25, 1, 0x02, 16]).map(|_| ())
+        );
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 3,
+                actual: 2
+            }),
+            decode_message_pack(&[0xF2, 25]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn decode_message_pack_nested() {
+        let mut pack = [0u8; 3 + 25];
+        pack[..3].copy_from_slice(&[0xF2, 25, 1]);
+        pack[3..6].copy_from_slice(&[0xF2, 25, 0]);
+
+        let mut messages = decode_message_pack(&pack).unwrap();
+        assert_eq!(
+            Some(Err(DecodeError::UnexpectedMessagePack)),
+            messages.next()
+        );
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 2,
+                actual: 0
+            }),
+            from_service_data(&[])
+        );
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 25,
+                actual: 1
+            }),
+            from_service_data(&[13, 0, 0x02])
+        );
+        assert_eq!(
+            Err(DecodeError::WrongAppCode(0x16)),
+            from_service_data(&[0x16; 27])
+        );
+
+        let mut buffer = [0u8; 25];
+        buffer[0] = 0x62;
+        assert_eq!(
+            Err(DecodeError::UnknownMessageType(6)),
+            from_message_buffer(&buffer)
+        );
+        buffer[0] = 0x03;
+        assert_eq!(
+            Err(DecodeError::UnsupportedProtocolVersion(3)),
+            from_message_buffer(&buffer)
+        );
+        buffer[0] = 0xF2;
+        assert_eq!(
+            Err(DecodeError::UnexpectedMessagePack),
+            from_message_buffer(&buffer)
+        );
+
+        // authentication page 0 claiming more pages than allowed
+        buffer[0] = 0x22;
+        buffer[2] = 16;
+        assert_eq!(
+            Err(DecodeError::FieldOutOfRange {
+                field: "last page index",
+                value: 16
+            }),
+            from_message_buffer(&buffer)
+        );
+
+        // location timestamp past the hour
+        let mut buffer = [0u8; 25];
+        buffer[0] = 0x12;
+        buffer[21..23].copy_from_slice(&36001u16.to_le_bytes());
+        assert_eq!(
+            Err(DecodeError::InvalidTimestamp),
+            from_message_buffer(&buffer)
+        );
+    }
+
+    #[test]
+    fn decode_strict_serial_number() {
+        use basic_id::{SerialNumberError, UasIdError};
+
+        let serial = SerialNumber::parse("1596F359746167260749").unwrap();
+        let basic_id = BasicId::new(UAType::Aeroplane, &serial.into());
+        let mut message = [0u8; MESSAGE_SIZE];
+        // Basic ID of F3411-19, serial number, aeroplane
+        message[1] = 0x11;
+        message[2..22].copy_from_slice(&basic_id.uas_id);
+        assert_eq!(
+            Ok((
+                ProtocolVersion::F3411_19,
+                RemoteIDMessage::BasicID(basic_id)
+            )),
+            decode_with_mode(&message, DecodeMode::Strict)
+        );
+
+        // DroneTag Mini, length code 0
+        message[2..22].copy_from_slice(b"10000000000000000009");
+        assert!(decode_with_mode(&message, DecodeMode::Lenient).is_ok());
+        assert_eq!(
+            Err(DecodeError::InvalidUasId(UasIdError::InvalidSerialNumber(
+                SerialNumberError::InvalidLengthCode('0')
+            ))),
+            decode_with_mode(&message, DecodeMode::Strict)
+        );
+
+        // reserved ID types are rejected as well, packs honour the mode
+        message[1] = 0x71;
+        assert_eq!(
+            Err(DecodeError::InvalidUasId(UasIdError::UnknownIdType(7))),
+            decode_with_mode(&message, DecodeMode::Strict)
+        );
+        let mut pack = [0xF0, MESSAGE_SIZE as u8, 1].to_vec();
+        pack.extend_from_slice(&message);
+        let mut messages = decode_message_pack(&pack).unwrap();
+        assert!(messages.clone().next().unwrap().is_ok());
+        messages = messages.with_mode(DecodeMode::Strict);
+        assert!(messages.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn peek_message_header() {
+        let mut location = [0u8; 25];
+        location[0] = 0x12;
+        assert_eq!(
+            Ok(MessageHeader {
+                kind: MessageKind::Location,
+                version: ProtocolVersion::F3411_22a,
+                counter: None,
+            }),
+            peek_header(&location)
+        );
+
+        let pack = [0xF1, 25, 0];
+        assert_eq!(
+            Ok(MessageHeader {
+                kind: MessageKind::MessagePack,
+                version: ProtocolVersion::F3411_20,
+                counter: None,
+            }),
+            peek_header(&pack)
+        );
+
+        // service data, the body is not looked at
+        assert_eq!(
+            Ok(MessageHeader {
+                kind: MessageKind::OperatorId,
+                version: ProtocolVersion::F3411_19,
+                counter: Some(200),
+            }),
+            peek_header(&[OPEN_DRONE_ID_AD_CODE, 200, 0x50])
+        );
+
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 3,
+                actual: 2
+            }),
+            peek_header(&[OPEN_DRONE_ID_AD_CODE, 200])
+        );
+        assert_eq!(
+            Err(DecodeError::UnknownMessageType(7)),
+            peek_header(&[0x72])
+        );
+        assert_eq!(
+            Err(DecodeError::UnsupportedProtocolVersion(3)),
+            peek_header(&[0x13])
+        );
+
+        for kind in 0..=0xF {
+            if let Ok(k) = MessageKind::try_from(kind) {
+                assert_eq!(kind, u8::from(k));
+            }
+        }
+    }
+
+    #[test]
+    fn decode_arbitrary_input_does_not_panic() {
+        let mut state = 0x2545_f491_u32;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        };
+
+        let mut buffer = [0u8; 3 + 9 * 25];
+        for header in 0..=u8::MAX {
+            for len in 0..buffer.len() {
+                buffer.iter_mut().for_each(|b| *b = random());
+                buffer[0] = header;
+
+                let _ = from_message_buffer(&buffer[..len]);
+                let _ = peek_header(&buffer[..len]);
+                if let Ok(pack) = decode_message_pack(&buffer[..len]) {
+                    pack.for_each(drop);
+                }
+
+                buffer[0] = OPEN_DRONE_ID_AD_CODE;
+                buffer[2] = header;
+                let _ = from_service_data(&buffer[..len]);
+            }
+        }
+    }
 }
@@ -1,78 +1,484 @@
+use authentication::{
+    AuthPage, AuthPayload, Authentication, MAX_PAGES, PAGE_DATA_SIZE, PAGE_ZERO_DATA_SIZE,
+};
 use basic_id::BasicId;
+use chrono::{DateTime, Utc};
+use coordinate::Coordinate;
 use location::HeightType;
 use location::Location;
-use location::OperationalStatus;
 
 use crate::data::operator_id::OperatorId;
 use crate::data::self_id::SelfId;
-use crate::data::system::ClassificationType;
 use crate::data::system::System;
+use crate::data::system::{ClassificationType, UaClassification};
 use crate::data::*;
 use crate::put_bits;
 use crate::MAX_ID_BYTE_SIZE;
 
-pub fn encode_message_pack(buf: &mut [u8], msgs: &[RemoteIDMessage]) -> Option<usize> {
-    if buf.len() < 1 + 2 + 25 * msgs.len() {
-        return None;
+use super::{decode, MAX_MESSAGES_IN_PACK, MESSAGE_SIZE, TIMESTAMP_EPOCH_2019};
+
+/// Reasons a message could not be encoded
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EncodeError {
+    /// The output buffer cannot hold the encoded messages
+    BufferTooSmall { expected: usize, actual: usize },
+    /// More messages were given than fit into a Message Pack
+    TooManyMessages(usize),
+    /// No messages were given where at least one is sent
+    NoMessages,
+    /// A field holds a value the message format cannot represent
+    FieldOutOfRange { field: &'static str, value: f64 },
+    /// A field holds information the targeted protocol version has no room for
+    UnsupportedField {
+        field: &'static str,
+        version: ProtocolVersion,
+    },
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EncodeError::BufferTooSmall { expected, actual } => {
+                write!(
+                    f,
+                    "buffer too small: expected {expected} bytes, got {actual}"
+                )
+            }
+            EncodeError::TooManyMessages(count) => {
+                write!(f, "too many messages for a message pack: {count}")
+            }
+            EncodeError::NoMessages => write!(f, "no messages to encode"),
+            EncodeError::FieldOutOfRange { field, value } => {
+                write!(f, "{field} out of range: {value}")
+            }
+            EncodeError::UnsupportedField { field, version } => {
+                write!(f, "{field} is not supported by {version:?}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {}
+
+/// How values the message format cannot represent are handled
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum EncodeMode {
+    /// Reject the message with [`EncodeError::FieldOutOfRange`], or with
+    /// [`EncodeError::UnsupportedField`] when the targeted version cannot carry a field
+    #[default]
+    Strict,
+    /// Encode the value as the closest limit of its field, or as the "unknown" value
+    /// where the specification defines one for invalid data (e.g. latitude/longitude 0,
+    /// timestamp 0xFFFF). Values that are not a number always become "unknown", fields
+    /// the targeted version cannot carry are left out.
+    Clamp,
+}
+
+impl EncodeMode {
+    /// Validate a value against the range of its field, or clamp it into that range.
+    ///
+    /// `None` stands for the "unknown" encoding of the field.
+    pub(crate) fn limit(
+        self,
+        field: &'static str,
+        value: Option<f32>,
+        min: f32,
+        max: f32,
+    ) -> Result<Option<f32>, EncodeError> {
+        match (self, value) {
+            (_, None) => Ok(None),
+            (_, Some(value)) if (min..=max).contains(&value) => Ok(Some(value)),
+            (EncodeMode::Strict, Some(value)) => Err(EncodeError::FieldOutOfRange {
+                field,
+                value: value as f64,
+            }),
+            (EncodeMode::Clamp, Some(value)) if value.is_nan() => Ok(None),
+            (EncodeMode::Clamp, Some(value)) => Ok(Some(value.clamp(min, max))),
+        }
+    }
+
+    /// Validate a value against the range of its field, or replace it with "unknown"
+    fn or_unknown(
+        self,
+        field: &'static str,
+        value: Option<f32>,
+        min: f32,
+        max: f32,
+    ) -> Result<Option<f32>, EncodeError> {
+        match (self, value) {
+            (_, None) => Ok(None),
+            (_, Some(value)) if (min..=max).contains(&value) => Ok(Some(value)),
+            (EncodeMode::Strict, Some(value)) => Err(EncodeError::FieldOutOfRange {
+                field,
+                value: value as f64,
+            }),
+            (EncodeMode::Clamp, Some(_)) => Ok(None),
+        }
+    }
+
+    /// Validate that an enum value fits into its `bits` wide field, or replace it with 0,
+    /// the undeclared/unknown value of all such fields
+    fn fit(self, field: &'static str, value: impl Into<u8>, bits: u32) -> Result<u8, EncodeError> {
+        let value = value.into();
+        match self {
+            _ if value >> bits == 0 => Ok(value),
+            EncodeMode::Strict => Err(EncodeError::FieldOutOfRange {
+                field,
+                value: value as f64,
+            }),
+            EncodeMode::Clamp => Ok(0),
+        }
     }
-    buf[0] = (0xF << 4) | 2;
-    buf[1] = 25;
+
+    /// Validate an integer against the range of its field, or clamp it into that range
+    fn bound(
+        self,
+        field: &'static str,
+        value: u8,
+        range: core::ops::RangeInclusive<u8>,
+    ) -> Result<u8, EncodeError> {
+        match self {
+            _ if range.contains(&value) => Ok(value),
+            EncodeMode::Strict => Err(EncodeError::FieldOutOfRange {
+                field,
+                value: value as f64,
+            }),
+            EncodeMode::Clamp => Ok(value.clamp(*range.start(), *range.end())),
+        }
+    }
+
+    /// Check that a field missing from `version` holds no information, or drop it
+    fn absent(
+        self,
+        field: &'static str,
+        present: bool,
+        version: ProtocolVersion,
+    ) -> Result<(), EncodeError> {
+        match self {
+            EncodeMode::Strict if present => Err(EncodeError::UnsupportedField { field, version }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Highest horizontal speed, 254 * 0.75 m/s + 63.75 m/s
+const MAX_SPEED: f32 = 254.25;
+
+/// Highest vertical speed in either direction
+const MAX_VERTICAL_SPEED: f32 = 62.;
+
+/// Round half away from zero, `f32::round` is not available without `std`
+pub(crate) fn round(value: f32) -> f32 {
+    if value >= 0. {
+        (value + 0.5) as i32 as f32
+    } else {
+        (value - 0.5) as i32 as f32
+    }
+}
+
+/// Highest altitude that fits the (altitude + 1000 m) / 0.5 encoding
+const MAX_ALTITUDE: f32 = u16::MAX as f32 * 0.5 - 1000.;
+
+/// Encode a Message Pack holding `msgs`, returning the number of bytes written
+pub fn encode_message_pack(
+    buf: &mut [u8],
+    msgs: &[RemoteIDMessage],
+    mode: EncodeMode,
+) -> Result<usize, EncodeError> {
+    encode_message_pack_versioned(buf, msgs, ProtocolVersion::default(), mode)
+}
+
+/// Encode a Message Pack holding `msgs` as `version`, returning the number of bytes written
+pub fn encode_message_pack_versioned(
+    buf: &mut [u8],
+    msgs: &[RemoteIDMessage],
+    version: ProtocolVersion,
+    mode: EncodeMode,
+) -> Result<usize, EncodeError> {
+    if msgs.len() > MAX_MESSAGES_IN_PACK {
+        return Err(EncodeError::TooManyMessages(msgs.len()));
+    }
+
+    let expected = 1 + 2 + MESSAGE_SIZE * msgs.len();
+    if buf.len() < expected {
+        return Err(EncodeError::BufferTooSmall {
+            expected,
+            actual: buf.len(),
+        });
+    }
+
+    buf[0] = (0xF << 4) | u8::from(version);
+    buf[1] = MESSAGE_SIZE as u8;
     buf[2] = msgs.len() as u8;
     let mut offset = 3;
     for msg in msgs {
-        buf[offset..(offset + 25)].copy_from_slice(&to_service_data(msg));
-        offset += 25;
+        buf[offset..(offset + MESSAGE_SIZE)]
+            .copy_from_slice(&encode_versioned(msg, version, mode)?);
+        offset += MESSAGE_SIZE;
     }
 
-    Some(offset)
+    Ok(offset)
 }
 
-pub fn to_service_data(msg: &RemoteIDMessage) -> [u8; 25] {
-    let mut data = [0u8; 25];
+/// Split an authentication payload into its pages, ready to be sent as individual messages
+pub fn authentication_pages(
+    payload: &AuthPayload,
+) -> impl ExactSizeIterator<Item = Authentication> + '_ {
+    let data = payload.data();
+    let last_page_index = if data.len() <= PAGE_ZERO_DATA_SIZE {
+        0
+    } else {
+        (data.len() - PAGE_ZERO_DATA_SIZE).div_ceil(PAGE_DATA_SIZE)
+    };
+
+    (0..(last_page_index + 1)).map(move |page_number| {
+        let page = if page_number == 0 {
+            let end = data.len().min(PAGE_ZERO_DATA_SIZE);
+            let mut page_data = [0u8; PAGE_ZERO_DATA_SIZE];
+            page_data[..end].copy_from_slice(&data[..end]);
 
-    let version = 2;
+            AuthPage::First {
+                last_page_index: last_page_index as u8,
+                length: data.len() as u8,
+                timestamp: payload.timestamp,
+                data: page_data,
+            }
+        } else {
+            let start = PAGE_ZERO_DATA_SIZE + (page_number - 1) * PAGE_DATA_SIZE;
+            let end = data.len().min(start + PAGE_DATA_SIZE);
+            let mut page_data = [0u8; PAGE_DATA_SIZE];
+            page_data[..(end - start)].copy_from_slice(&data[start..end]);
+
+            AuthPage::Continuation {
+                page_number: page_number as u8,
+                data: page_data,
+            }
+        };
+
+        Authentication {
+            auth_type: payload.auth_type,
+            page,
+        }
+    })
+}
+
+/// Encode all pages of an authentication payload as consecutive 25 byte messages
+pub fn encode_authentication(buf: &mut [u8], payload: &AuthPayload) -> Result<usize, EncodeError> {
+    let pages = authentication_pages(payload);
+    let expected = pages.len() * MESSAGE_SIZE;
+    if buf.len() < expected {
+        return Err(EncodeError::BufferTooSmall {
+            expected,
+            actual: buf.len(),
+        });
+    }
+
+    let mut offset = 0;
+    for page in pages {
+        let msg = RemoteIDMessage::Authentication(page);
+        buf[offset..(offset + MESSAGE_SIZE)]
+            .copy_from_slice(&encode_message(&msg, EncodeMode::Strict)?);
+        offset += MESSAGE_SIZE;
+    }
+
+    Ok(offset)
+}
+
+/// Encode a single message, values the format cannot represent are clamped.
+///
+/// See [`EncodeMode::Clamp`], use [`encode_message`] to reject such values instead.
+pub fn to_service_data(msg: &RemoteIDMessage) -> [u8; MESSAGE_SIZE] {
+    encode_message(msg, EncodeMode::Clamp).expect("clamping accepts every value")
+}
+
+/// Encode a single message as the latest protocol version
+pub fn encode_message(
+    msg: &RemoteIDMessage,
+    mode: EncodeMode,
+) -> Result<[u8; MESSAGE_SIZE], EncodeError> {
+    encode_versioned(msg, ProtocolVersion::default(), mode)
+}
+
+/// Encode a single message as `version`
+pub fn encode_versioned(
+    msg: &RemoteIDMessage,
+    version: ProtocolVersion,
+    mode: EncodeMode,
+) -> Result<[u8; MESSAGE_SIZE], EncodeError> {
+    let mut data = [0u8; MESSAGE_SIZE];
+
+    let version_nibble = u8::from(version);
 
     match msg {
         RemoteIDMessage::BasicID(basic_id) => {
-            data[0] = (basic_id::MESSAGE_TYPE << 4) | version;
-            encode_basic_id(basic_id, &mut data[0..]);
+            data[0] = (basic_id::MESSAGE_TYPE << 4) | version_nibble;
+            encode_basic_id(basic_id, &mut data[0..], mode)?;
         }
         RemoteIDMessage::Location(location) => {
-            data[0] = (location::MESSAGE_TYPE << 4) | version;
-            encode_location(location, &mut data[0..]);
+            data[0] = (location::MESSAGE_TYPE << 4) | version_nibble;
+            encode_location(location, &mut data[0..], version, mode)?;
+        }
+        RemoteIDMessage::Authentication(auth) => {
+            data[0] = (authentication::MESSAGE_TYPE << 4) | version_nibble;
+            encode_authentication_page(auth, &mut data[0..], mode)?;
         }
         RemoteIDMessage::System(system) => {
-            data[0] = (system::MESSAGE_TYPE << 4) | version;
-            encode_system(system, &mut data[0..]);
+            data[0] = (system::MESSAGE_TYPE << 4) | version_nibble;
+            encode_system(system, &mut data[0..], version, mode)?;
         }
         RemoteIDMessage::SelfId(self_id) => {
-            data[0] = (self_id::MESSAGE_TYPE << 4) | version;
+            data[0] = (self_id::MESSAGE_TYPE << 4) | version_nibble;
             encode_self_id(self_id, &mut data[0..]);
         }
         RemoteIDMessage::OperatorId(operator_id) => {
-            data[0] = (operator_id::MESSAGE_TYPE << 4) | version;
+            data[0] = (operator_id::MESSAGE_TYPE << 4) | version_nibble;
             encode_operator_id(operator_id, &mut data[0..]);
         }
     }
 
-    data
+    Ok(data)
+}
+
+/// The Location a receiver decodes after `location` is sent.
+///
+/// Every value is rounded to the resolution of its field, out of range values are clamped as
+/// with [`EncodeMode::Clamp`].
+pub fn quantize(location: &Location) -> Location {
+    let msg = RemoteIDMessage::Location(location.clone());
+    match decode::from_message_buffer(&to_service_data(&msg)) {
+        Ok(RemoteIDMessage::Location(location)) => location,
+        _ => unreachable!("an encoded Location decodes as a Location"),
+    }
+}
+
+/// Seconds since 00:00:00 01/01/2019 as used by System and Authentication timestamps
+fn timestamp_since_2019(
+    field: &'static str,
+    timestamp: &DateTime<Utc>,
+    mode: EncodeMode,
+) -> Result<u32, EncodeError> {
+    let secs = timestamp.timestamp() - TIMESTAMP_EPOCH_2019;
+    match mode {
+        _ if (0..=u32::MAX as i64).contains(&secs) => Ok(secs as u32),
+        EncodeMode::Strict => Err(EncodeError::FieldOutOfRange {
+            field,
+            value: timestamp.timestamp() as f64,
+        }),
+        EncodeMode::Clamp => Ok(secs.clamp(0, u32::MAX as i64) as u32),
+    }
 }
 
-fn encode_basic_id(msg: &BasicId, target: &mut [u8]) {
-    let first_nibble: u8 = msg.id_type.into();
-    let last_nibble: u8 = msg.ua_type.into();
+/// Encode a latitude or longitude, `None` is "unknown" and sent as 0
+fn encode_coordinate(
+    field: &'static str,
+    coordinate: Option<Coordinate>,
+    limit: f64,
+    mode: EncodeMode,
+) -> Result<i32, EncodeError> {
+    let Some(coordinate) = coordinate else {
+        return Ok(0);
+    };
+
+    let limit = Coordinate::from_degrees(limit).raw();
+    match mode {
+        _ if (-limit..=limit).contains(&coordinate.raw()) => Ok(coordinate.raw()),
+        EncodeMode::Strict => Err(EncodeError::FieldOutOfRange {
+            field,
+            value: coordinate.degrees(),
+        }),
+        EncodeMode::Clamp => Ok(0),
+    }
+}
+
+pub(crate) fn encode_latitude(
+    latitude: Option<Coordinate>,
+    mode: EncodeMode,
+) -> Result<i32, EncodeError> {
+    encode_coordinate("latitude", latitude, 90., mode)
+}
+
+pub(crate) fn encode_longitude(
+    longitude: Option<Coordinate>,
+    mode: EncodeMode,
+) -> Result<i32, EncodeError> {
+    encode_coordinate("longitude", longitude, 180., mode)
+}
+
+/// Encode an altitude as (altitude + 1000 m) / 0.5, `None` is "unknown" and sent as -1000 m
+fn encode_altitude(
+    field: &'static str,
+    altitude: Option<f32>,
+    mode: EncodeMode,
+) -> Result<u16, EncodeError> {
+    let altitude = mode.limit(field, altitude, -1000., MAX_ALTITUDE)?;
+    Ok(
+        altitude.map_or(0, |altitude| match round((altitude + 1000.) / 0.5) as u16 {
+            // only -1000 m itself is sent as "unknown"
+            0 if altitude > -1000. => 1,
+            raw => raw,
+        }),
+    )
+}
+
+fn encode_basic_id(msg: &BasicId, target: &mut [u8], mode: EncodeMode) -> Result<(), EncodeError> {
+    let first_nibble = mode.fit("id type", msg.id_type, 4)?;
+    let last_nibble = mode.fit("ua type", msg.ua_type, 4)?;
     target[1] = (first_nibble << 4) | last_nibble;
 
     target[2..(MAX_ID_BYTE_SIZE + 2)].clone_from_slice(&msg.uas_id);
+
+    Ok(())
 }
 
-fn encode_self_id(msg: &SelfId, target: &mut [u8]) {
-    match msg.description {
-        self_id::Description::Text(txt) => {
-            target[1] = 0;
-            target[2..25].copy_from_slice(&txt);
+fn encode_authentication_page(
+    msg: &Authentication,
+    target: &mut [u8],
+    mode: EncodeMode,
+) -> Result<(), EncodeError> {
+    // Authentication Type: Bits [7..4]
+    // Page Number: Bits [3..0]
+    let auth_type = mode.fit("authentication type", msg.auth_type, 4)?;
+    let last_index = MAX_PAGES as u8 - 1;
+    let page_number = match &msg.page {
+        AuthPage::First { .. } => 0,
+        AuthPage::Continuation { page_number, .. } => {
+            mode.bound("page number", *page_number, 1..=last_index)?
         }
     };
+    target[1] = put_bits!(auth_type, 7..4) | put_bits!(page_number, 3..0);
+
+    match &msg.page {
+        AuthPage::First {
+            last_page_index,
+            length,
+            timestamp,
+            data,
+        } => {
+            let last_page_index =
+                mode.bound("last page index", *last_page_index, 0..=last_index)?;
+            // the data bytes the pages can hold
+            let capacity = PAGE_ZERO_DATA_SIZE + last_page_index as usize * PAGE_DATA_SIZE;
+            let capacity = capacity.min(u8::MAX as usize) as u8;
+            target[2] = last_page_index;
+            target[3] = mode.bound("authentication length", *length, 0..=capacity)?;
+            let secs = timestamp_since_2019("authentication timestamp", timestamp, mode)?;
+            target[4..8].copy_from_slice(&secs.to_le_bytes());
+            target[8..25].copy_from_slice(data);
+        }
+        AuthPage::Continuation { data, .. } => {
+            target[2..25].copy_from_slice(data);
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_self_id(msg: &SelfId, target: &mut [u8]) {
+    // Description Type
+    target[1] = msg.description.description_type();
+
+    // Description
+    target[2..25].copy_from_slice(msg.description.as_bytes());
 }
 
 fn encode_operator_id(msg: &OperatorId, target: &mut [u8]) {
@@ -81,72 +487,107 @@ fn encode_operator_id(msg: &OperatorId, target: &mut [u8]) {
     // 22..25 is reserved
 }
 
-fn encode_location(msg: &Location, target: &mut [u8]) {
-    let operational_status: u8 = OperationalStatus::into(msg.operational_status);
+fn encode_location(
+    msg: &Location,
+    target: &mut [u8],
+    version: ProtocolVersion,
+    mode: EncodeMode,
+) -> Result<(), EncodeError> {
+    let operational_status = mode.fit("operational status", msg.operational_status, 4)?;
     let height_type: u8 = HeightType::into(msg.height_type);
-    let ew_direction_segment: u8 = if msg.track_direction > 180 { 1 } else { 0 };
+    // 0..179 degrees with the East/West direction segment set for 180 and above
+    let track_direction = mode.or_unknown(
+        "track direction",
+        msg.track_direction.map(|d| d as f32),
+        0.,
+        360.,
+    )?;
+    let (ew_direction_segment, track_direction): (u8, u8) = match track_direction {
+        // 361 is "unknown"
+        None => (1, 181),
+        Some(d) => match round(d) as u16 % 360 {
+            d @ 0..180 => (0, d as u8),
+            d => (1, (d - 180) as u8),
+        },
+    };
 
-    let speed_multiplier: u8 = if msg.speed > 255. * 0.25 { 1 } else { 0 };
+    // Speed
+    //   0.25 m/s steps up to 63.75 m/s, then 0.75 m/s steps with the multiplier set
+    let speed = mode.limit("speed", msg.speed, 0., MAX_SPEED)?;
+    let (speed_multiplier, speed): (u8, u8) = match speed {
+        // 255 with the multiplier set is "unknown"
+        None => (1, 255),
+        Some(speed) if speed <= 255. * 0.25 => (0, round(speed / 0.25) as u8),
+        Some(speed) => (1, (round((speed - 255. * 0.25) / 0.75) as u8).min(254)),
+    };
 
     target[1] =
-        operational_status << 3 | height_type << 2 | ew_direction_segment << 1 | speed_multiplier;
+        operational_status << 4 | height_type << 2 | ew_direction_segment << 1 | speed_multiplier;
 
     // Track Direction
-    target[2] = if msg.track_direction > 180 {
-        (msg.track_direction - 180) as u8
-    } else {
-        msg.track_direction as u8
-    };
+    target[2] = track_direction;
 
     // Speed
-    target[3] = if msg.speed <= 255. * 0.25 {
-        (msg.speed * 0.25) as u8
-    } else if msg.speed > 255. * 0.25 && msg.speed < 254.25 {
-        ((msg.speed - (255. * 0.25)) / 0.75) as u8
-    } else {
-        254
-    };
+    target[3] = speed;
 
     // Vertical Speed
-    target[4] = (msg.vertical_speed / 0.5) as u8;
+    //   Signed 0.5 m/s steps, 63 m/s is "unknown"
+    let vertical_speed = mode.limit(
+        "vertical speed",
+        msg.vertical_speed,
+        -MAX_VERTICAL_SPEED,
+        MAX_VERTICAL_SPEED,
+    )?;
+    target[4] = round(vertical_speed.unwrap_or(63.) / 0.5) as i8 as u8;
 
     // Latitude
-    let lat = (msg.latidute * 1e7) as i32;
+    let lat = encode_latitude(msg.latidute, mode)?;
     target[5..9].clone_from_slice(&lat.to_le_bytes());
 
     // Longitude
-    let lon = (msg.longitude * 1e7) as i32;
+    let lon = encode_longitude(msg.longitude, mode)?;
     target[9..13].clone_from_slice(&lon.to_le_bytes());
 
     // Pressure Altitude
-    let pressure_altitude = ((msg.pressure_altitude + 1000.) / 0.5) as u16;
+    let pressure_altitude = encode_altitude("pressure altitude", msg.pressure_altitude, mode)?;
     target[13..15].clone_from_slice(&pressure_altitude.to_le_bytes());
 
     // Geodetic Altitude
-    let geodetic_altitude = ((msg.geodetic_altitude + 1000.) / 0.5) as u16;
+    let geodetic_altitude = encode_altitude("geodetic altitude", msg.geodetic_altitude, mode)?;
     target[15..17].clone_from_slice(&geodetic_altitude.to_le_bytes());
 
     // Height
-    let height = ((msg.height + 1000.) / 0.5) as u16;
+    let height = encode_altitude("height", msg.height, mode)?;
     target[17..19].clone_from_slice(&height.to_le_bytes());
 
     // Vertical / Horizontal Accuracy
-    let vertical_accuracy: u8 = msg.vertical_accuracy.into();
-    let horizontal_accuracy: u8 = msg.horizontal_accuracy.into();
+    let vertical_accuracy = mode.fit("vertical accuracy", msg.vertical_accuracy, 4)?;
+    let horizontal_accuracy = mode.fit("horizontal accuracy", msg.horizontal_accuracy, 4)?;
     target[19] = vertical_accuracy << 4 | horizontal_accuracy;
 
     // Baro Altitude Accuracy / Speed Accuracy
-    let baro_altitude_accuracy: u8 = msg.baro_altitude_accuracy.into();
-    let speed_accuracy: u8 = msg.speed_accuracy.into();
+    let baro_altitude_accuracy =
+        mode.fit("baro altitude accuracy", msg.baro_altitude_accuracy, 4)?;
+    let speed_accuracy = mode.fit("speed accuracy", msg.speed_accuracy, 4)?;
     target[20] = baro_altitude_accuracy << 4 | speed_accuracy;
 
     // Timestamp
-    let timestamp = (msg.timestamp * 10.) as u16;
+    //   1/10ths of seconds since the last hour, 0xFFFF is "unknown"
+    let timestamp = mode.or_unknown("timestamp", msg.timestamp, 0., 3600.)?;
+    let timestamp = timestamp.map_or(u16::MAX, |timestamp| round(timestamp * 10.) as u16);
     target[21..23].clone_from_slice(&timestamp.to_le_bytes());
 
-    // Reserved / Timestamp Accuracy
-    let timestamp_accuracy = if let Some(acc) = msg.timestamp_accuracy {
-        (acc.as_secs_f32() / 0.1) as u8
+    // Reserved / Timestamp Accuracy, the whole byte is reserved in F3411-19
+    let timestamp_accuracy = if version == ProtocolVersion::F3411_19 {
+        mode.absent(
+            "timestamp accuracy",
+            msg.timestamp_accuracy.is_some(),
+            version,
+        )?;
+        0
+    } else if let Some(acc) = msg.timestamp_accuracy {
+        let acc = mode.limit("timestamp accuracy", Some(acc.as_secs_f32()), 0., 1.5)?;
+        acc.map_or(0, |acc| round(acc / 0.1) as u8)
     } else {
         0
     };
@@ -154,44 +595,74 @@ fn encode_location(msg: &Location, target: &mut [u8]) {
 
     // Reserved
     target[24] = 0;
+
+    Ok(())
 }
 
-fn encode_system(msg: &System, target: &mut [u8]) {
-    // Classification Type: Bits [4..2]
+fn encode_system(
+    msg: &System,
+    target: &mut [u8],
+    version: ProtocolVersion,
+    mode: EncodeMode,
+) -> Result<(), EncodeError> {
+    // F3411-19 ends the message after the area floor, everything from byte 17 on is reserved
+    let v19 = version == ProtocolVersion::F3411_19;
+    if v19 {
+        let undeclared = msg.classification_type == ClassificationType::Undeclared
+            && msg.ua_classification == UaClassification::undefined();
+        mode.absent("ua classification", !undeclared, version)?;
+        mode.absent(
+            "operator altitude",
+            msg.operator_altitude != -1000.,
+            version,
+        )?;
+        mode.absent("timestamp", msg.timestamp.is_some(), version)?;
+    }
+
+    // Classification Type: Bits [4..2], reserved in F3411-19
     // Operator Location/Altitude source type: Bits [1..0]
-    let classification_type: u8 = msg.classification_type as u8;
-    let operator_location_type: u8 = msg.operator_location_type as u8;
+    let classification_type = if v19 {
+        0
+    } else {
+        mode.fit("classification type", msg.classification_type, 3)?
+    };
+    let operator_location_type =
+        mode.fit("operator location type", msg.operator_location_type, 2)?;
     target[1] = (classification_type << 2) | operator_location_type;
 
     // Operator Latitude
-    let lat = (msg.operator_latidute * 10000000.) as u32;
+    let lat = encode_latitude(Some(msg.operator_latidute), mode)?;
     target[2..6].clone_from_slice(&lat.to_le_bytes());
 
     // Operator Longitude
-    let lon = (msg.operator_longitude * 10000000.) as u32;
+    let lon = encode_longitude(Some(msg.operator_longitude), mode)?;
     target[6..10].clone_from_slice(&lon.to_le_bytes());
 
     // Area Count
     target[10..12].clone_from_slice(&msg.area_count.to_le_bytes());
 
     // Area Radius
-    let area_radius = (msg.area_radius / 10.) as u8;
+    let area_radius = mode.limit("area radius", Some(msg.area_radius), 0., 2550.)?;
+    let area_radius = area_radius.map_or(0, |radius| round(radius / 10.) as u8);
     target[12] = area_radius;
 
     // Area Ceiling
-    let area_ceiling = ((msg.area_ceiling + 1000.) / 0.5) as u16;
+    let area_ceiling = encode_altitude("area ceiling", Some(msg.area_ceiling), mode)?;
     target[13..15].clone_from_slice(&area_ceiling.to_le_bytes());
 
     // Area Floor
-    let area_floor = ((msg.area_floor + 1000.) / 0.5) as u16;
+    let area_floor = encode_altitude("area floor", Some(msg.area_floor), mode)?;
     target[15..17].clone_from_slice(&area_floor.to_le_bytes());
 
+    if v19 {
+        target[17..25].fill(0);
+        return Ok(());
+    }
+
     // UA Classification
-    target[17] = if msg.classification_type == ClassificationType::EuropeanUnion {
-        let (cat, class): (u8, u8) = (
-            msg.ua_classification.category.into(),
-            msg.ua_classification.class.into(),
-        );
+    target[17] = if msg.classification_type != ClassificationType::Undeclared {
+        let cat = mode.fit("ua category", msg.ua_classification.category, 4)?;
+        let class = mode.fit("ua class", msg.ua_classification.class, 4)?;
 
         put_bits!(cat, 7..4) | put_bits!(class, 3..0)
     } else {
@@ -199,15 +670,28 @@ fn encode_system(msg: &System, target: &mut [u8]) {
     };
 
     // Operator Altitude
-    let operator_altitude = ((msg.operator_altitude + 1000.) / 0.5) as u16;
+    let operator_altitude =
+        encode_altitude("operator altitude", Some(msg.operator_altitude), mode)?;
     target[18..20].clone_from_slice(&operator_altitude.to_le_bytes());
 
     // Timestamp
-    let unix_timestamp = (msg.timestamp.timestamp() - 1546300800) as u32;
+    //   there is no "unknown" value, Clamp sends 00:00:00 01/01/2019 instead
+    let unix_timestamp = match (&msg.timestamp, mode) {
+        (Some(timestamp), _) => timestamp_since_2019("timestamp", timestamp, mode)?,
+        (None, EncodeMode::Strict) => {
+            return Err(EncodeError::UnsupportedField {
+                field: "unknown timestamp",
+                version,
+            })
+        }
+        (None, EncodeMode::Clamp) => 0,
+    };
     target[20..24].clone_from_slice(&unix_timestamp.to_le_bytes());
 
     // Reserved
     target[24] = 0;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -216,15 +700,29 @@ mod test {
 
     use chrono::DateTime;
 
+    use super::authentication::{
+        AuthPage, AuthType, Authentication, PAGE_DATA_SIZE, PAGE_ZERO_DATA_SIZE,
+    };
     use super::basic_id::{IdType, UAType};
-    use super::location::{HeightType, Location, OperationalStatus};
-    use crate::codec::copy_to_id;
-    use crate::codec::encode::to_service_data;
+    use super::location::{
+        HeightType, HorizontalAccuracy, Location, OperationalStatus, SpeedAccuracy,
+        VerticalAccuracy,
+    };
+    use crate::codec::decode::{decode_message_pack, decode_versioned, from_message_buffer};
+    use crate::codec::encode::{
+        encode_message, encode_message_pack, encode_message_pack_versioned, encode_versioned,
+        quantize, to_service_data, EncodeError, EncodeMode,
+    };
+    use crate::codec::{copy_to_id, TIMESTAMP_EPOCH_2019};
     use crate::data::basic_id::BasicId;
+    use crate::data::coordinate::Coordinate;
+    use crate::data::operator_id::OperatorIdType;
+    use crate::data::self_id::{Description, SelfId};
     use crate::data::system::{
         ClassificationType, OperatorLocationType, System, UaCategory, UaClass, UaClassification,
     };
-    use crate::data::RemoteIDMessage;
+    use crate::data::{ProtocolVersion, RemoteIDMessage, UnknownValue};
+    use crate::put_bits;
 
     #[test]
     fn encode_basic_id() {
@@ -264,20 +762,20 @@ mod test {
     fn encode_location_1() {
         let location = RemoteIDMessage::Location(Location {
             height_type: HeightType::AboveTakeoff,
-            operational_status: OperationalStatus::RemoteIdSystemFailure,
-            speed: 10.,
-            vertical_speed: 10.,
-            pressure_altitude: 190.5,
-            geodetic_altitude: 210.0,
+            operational_status: OperationalStatus::Airborne,
+            speed: Some(0.5),
+            vertical_speed: Some(10.),
+            pressure_altitude: Some(190.5),
+            geodetic_altitude: Some(210.0),
             baro_altitude_accuracy: crate::data::location::VerticalAccuracy::Unknown,
             horizontal_accuracy: crate::data::location::HorizontalAccuracy::LessThan_3_m,
             speed_accuracy: crate::data::location::SpeedAccuracy::LessThan_third_mps,
             vertical_accuracy: crate::data::location::VerticalAccuracy::LessThan_3_m,
-            track_direction: 77,
-            latidute: 49.874855,
-            longitude: 8.912173,
-            height: 0.,
-            timestamp: 361.0,
+            track_direction: Some(77),
+            latidute: Some(Coordinate::from_raw(498748544)),
+            longitude: Some(Coordinate::from_raw(89121736)),
+            height: Some(0.),
+            timestamp: Some(361.0),
             timestamp_accuracy: None,
         });
         let expected = [
@@ -288,12 +786,26 @@ mod test {
     }
 
     #[test]
-    fn encode_system() {
-        let system = RemoteIDMessage::System(System {
+    fn encode_self_id() {
+        let mut text = [0u8; 23];
+        text[..12].copy_from_slice("Drone survey".as_bytes());
+        let self_id = RemoteIDMessage::SelfId(SelfId {
+            description: Description::PrivateUse(222, text),
+        });
+
+        let expected = [
+            50, 222, 68, 114, 111, 110, 101, 32, 115, 117, 114, 118, 101, 121, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0,
+        ];
+        assert_eq!(expected, to_service_data(&self_id));
+    }
+
+    fn system() -> System {
+        System {
             classification_type: ClassificationType::EuropeanUnion,
             operator_location_type: OperatorLocationType::TakeOff,
-            operator_latidute: 49.874855,
-            operator_longitude: 8.912173,
+            operator_latidute: Coordinate::from_raw(498748544),
+            operator_longitude: Coordinate::from_raw(89121736),
             operator_altitude: 210.,
             area_ceiling: -1000.,
             area_count: 1,
@@ -303,10 +815,17 @@ mod test {
                 category: UaCategory::Open,
                 class: UaClass::Undefined,
             },
-            timestamp: DateTime::parse_from_rfc3339(&"2024-07-04T14:05:54Z")
-                .unwrap()
-                .to_utc(),
-        });
+            timestamp: Some(
+                DateTime::parse_from_rfc3339("2024-07-04T14:05:54Z")
+                    .unwrap()
+                    .to_utc(),
+            ),
+        }
+    }
+
+    #[test]
+    fn encode_system() {
+        let system = RemoteIDMessage::System(system());
 
         let service_data = [
             66, 4, 128, 76, 186, 29, 200, 227, 79, 5, 1, 0, 25, 0, 0, 0, 0, 16, 116, 9, 194, 254,
@@ -314,4 +833,571 @@ mod test {
         ];
         assert_eq!(service_data, to_service_data(&system));
     }
+
+    fn location() -> Location {
+        Location {
+            height_type: HeightType::AboveTakeoff,
+            operational_status: OperationalStatus::Airborne,
+            speed: Some(10.),
+            vertical_speed: Some(-2.),
+            pressure_altitude: Some(190.5),
+            geodetic_altitude: Some(210.0),
+            baro_altitude_accuracy: crate::data::location::VerticalAccuracy::Unknown,
+            horizontal_accuracy: crate::data::location::HorizontalAccuracy::LessThan_3_m,
+            speed_accuracy: crate::data::location::SpeedAccuracy::LessThan_third_mps,
+            vertical_accuracy: crate::data::location::VerticalAccuracy::LessThan_3_m,
+            track_direction: Some(77),
+            latidute: Some(Coordinate::from_raw(498748544)),
+            longitude: Some(Coordinate::from_raw(89121736)),
+            height: Some(0.),
+            timestamp: Some(361.0),
+            timestamp_accuracy: None,
+        }
+    }
+
+    #[test]
+    fn encode_location_out_of_range() {
+        for (location, field, value) in [
+            (
+                Location {
+                    geodetic_altitude: Some(-1200.),
+                    ..location()
+                },
+                "geodetic altitude",
+                -1200.,
+            ),
+            (
+                Location {
+                    speed: Some(300.),
+                    ..location()
+                },
+                "speed",
+                300.,
+            ),
+            (
+                Location {
+                    vertical_speed: Some(-80.),
+                    ..location()
+                },
+                "vertical speed",
+                -80.,
+            ),
+            (
+                Location {
+                    latidute: Some(Coordinate::from_degrees(91.)),
+                    ..location()
+                },
+                "latitude",
+                91.,
+            ),
+            (
+                Location {
+                    timestamp: Some(3700.),
+                    ..location()
+                },
+                "timestamp",
+                3700.,
+            ),
+        ] {
+            assert_eq!(
+                Err(EncodeError::FieldOutOfRange { field, value }),
+                encode_message(&RemoteIDMessage::Location(location), EncodeMode::Strict)
+            );
+        }
+    }
+
+    #[test]
+    fn encode_location_clamp() {
+        let location = RemoteIDMessage::Location(Location {
+            pressure_altitude: Some(-1200.),
+            geodetic_altitude: Some(40000.),
+            vertical_speed: Some(-80.),
+            latidute: Some(Coordinate::from_degrees(91.)),
+            timestamp: Some(f32::NAN),
+            ..location()
+        });
+
+        let data = encode_message(&location, EncodeMode::Clamp).unwrap();
+        assert_eq!(data, to_service_data(&location));
+
+        // -1000 m is "unknown", the highest altitude is 31767.5 m
+        assert_eq!([0, 0], data[13..15]);
+        assert_eq!([0xFF, 0xFF], data[15..17]);
+        // -62 m/s
+        assert_eq!(-124, data[4] as i8);
+        // invalid latitude becomes 0
+        assert_eq!([0, 0, 0, 0], data[5..9]);
+        // unknown timestamp
+        assert_eq!([0xFF, 0xFF], data[21..23]);
+    }
+
+    #[test]
+    fn encode_message_pack_errors() {
+        let msg = RemoteIDMessage::Location(location());
+        let mut buf = [0u8; 3 + 10 * 25];
+
+        assert_eq!(
+            Err(EncodeError::TooManyMessages(10)),
+            encode_message_pack(
+                &mut buf,
+                &core::array::from_fn::<_, 10, _>(|_| msg.clone()),
+                EncodeMode::Strict
+            )
+        );
+        assert_eq!(
+            Err(EncodeError::BufferTooSmall {
+                expected: 53,
+                actual: 30
+            }),
+            encode_message_pack(
+                &mut buf[..30],
+                &[msg.clone(), msg.clone()],
+                EncodeMode::Strict
+            )
+        );
+        assert_eq!(
+            Ok(53),
+            encode_message_pack(&mut buf, &[msg.clone(), msg], EncodeMode::Strict)
+        );
+    }
+
+    #[test]
+    fn encode_coordinates_round_trip() {
+        let location = [
+            18, 32, 77, 2, 20, 143, 76, 186, 29, 192, 227, 79, 5, 77, 9, 116, 9, 208, 7, 91, 4, 26,
+            14, 0, 0,
+        ];
+        let msg = from_message_buffer(&location).unwrap();
+        assert_eq!(location[5..13], to_service_data(&msg)[5..13]);
+
+        let system = [
+            66, 4, 131, 76, 186, 29, 188, 227, 79, 5, 1, 0, 25, 0, 0, 0, 0, 16, 116, 9, 194, 254,
+            91, 10, 0,
+        ];
+        let msg = from_message_buffer(&system).unwrap();
+        assert_eq!(system[2..10], to_service_data(&msg)[2..10]);
+    }
+
+    #[test]
+    fn encode_location_unknown_values() {
+        let location = Location {
+            speed: None,
+            vertical_speed: None,
+            pressure_altitude: None,
+            geodetic_altitude: None,
+            track_direction: None,
+            latidute: None,
+            longitude: None,
+            height: None,
+            timestamp: None,
+            ..location()
+        };
+        let msg = RemoteIDMessage::Location(location);
+
+        let data = encode_message(&msg, EncodeMode::Strict).unwrap();
+        // EW direction segment and speed multiplier set
+        assert_eq!(0b11, data[1] & 0b11);
+        // 361 degrees
+        assert_eq!(181, data[2]);
+        // 255 m/s
+        assert_eq!(255, data[3]);
+        // 63 m/s
+        assert_eq!(126, data[4]);
+        assert_eq!([0; 8], data[5..13]);
+        assert_eq!([0; 6], data[13..19]);
+        assert_eq!([0xFF, 0xFF], data[21..23]);
+
+        assert_eq!(Ok(msg), from_message_buffer(&data));
+    }
+
+    #[test]
+    fn encode_location_kinematics() {
+        for (speed, multiplier, raw) in [
+            (0., 0, 0),
+            (0.3, 0, 1),
+            (63.75, 0, 255),
+            (64., 1, 0),
+            (100., 1, 48),
+            (254.25, 1, 254),
+        ] {
+            let location = RemoteIDMessage::Location(Location {
+                speed: Some(speed),
+                ..location()
+            });
+            let data = encode_message(&location, EncodeMode::Strict).unwrap();
+            assert_eq!((multiplier, raw), (data[1] & 1, data[3]), "speed {speed}");
+        }
+
+        for (vertical_speed, raw) in [(-62., -124), (-2., -4), (-0.3, -1), (0.2, 0), (62., 124)] {
+            let location = RemoteIDMessage::Location(Location {
+                vertical_speed: Some(vertical_speed),
+                ..location()
+            });
+            let data = encode_message(&location, EncodeMode::Strict).unwrap();
+            assert_eq!(raw, data[4] as i8, "vertical speed {vertical_speed}");
+        }
+
+        for (direction, segment, raw) in [(0, 0, 0), (179, 0, 179), (180, 1, 0), (359, 1, 179)] {
+            let location = RemoteIDMessage::Location(Location {
+                track_direction: Some(direction),
+                ..location()
+            });
+            let data = encode_message(&location, EncodeMode::Strict).unwrap();
+            assert_eq!(
+                (segment, raw),
+                ((data[1] >> 1) & 1, data[2]),
+                "direction {direction}"
+            );
+        }
+    }
+
+    #[test]
+    fn quantize_location() {
+        let moving = Location {
+            speed: Some(100.),
+            vertical_speed: Some(-3.7),
+            track_direction: Some(200),
+            ..location()
+        };
+
+        let quantized = quantize(&moving);
+        assert_eq!(Some(99.75), quantized.speed);
+        assert_eq!(Some(-3.5), quantized.vertical_speed);
+        assert_eq!(Some(200), quantized.track_direction);
+        assert_eq!(quantized, quantize(&quantized));
+
+        let quantized = quantize(&Location {
+            speed: Some(500.),
+            vertical_speed: Some(-80.),
+            ..location()
+        });
+        assert_eq!(Some(254.25), quantized.speed);
+        assert_eq!(Some(-62.), quantized.vertical_speed);
+
+        let quantized = quantize(&Location {
+            pressure_altitude: Some(100.4),
+            geodetic_altitude: Some(-999.8),
+            height: Some(-999.6),
+            track_direction: Some(359),
+            timestamp: Some(1234.56),
+            timestamp_accuracy: Some(core::time::Duration::from_millis(290)),
+            ..location()
+        });
+        assert_eq!(Some(100.5), quantized.pressure_altitude);
+        assert_eq!(Some(-999.5), quantized.geodetic_altitude);
+        assert_eq!(Some(-999.5), quantized.height);
+        assert_eq!(Some(359), quantized.track_direction);
+        assert_eq!(Some(1234.6), quantized.timestamp);
+        assert_eq!(
+            Some(300),
+            quantized.timestamp_accuracy.map(|acc| acc.as_millis())
+        );
+    }
+
+    #[test]
+    fn system_coordinates_all_hemispheres() {
+        let system = |lat: f64, lon: f64| System {
+            classification_type: ClassificationType::EuropeanUnion,
+            operator_location_type: OperatorLocationType::TakeOff,
+            operator_latidute: Coordinate::from_degrees(lat),
+            operator_longitude: Coordinate::from_degrees(lon),
+            operator_altitude: 210.,
+            area_ceiling: -1000.,
+            area_count: 1,
+            area_floor: -1000.,
+            area_radius: 250.,
+            ua_classification: UaClassification {
+                category: UaCategory::Open,
+                class: UaClass::Undefined,
+            },
+            timestamp: Some(
+                DateTime::parse_from_rfc3339("2024-07-04T14:05:54Z")
+                    .unwrap()
+                    .to_utc(),
+            ),
+        };
+
+        for (lat, lon, expected) in [
+            // Santiago, south west
+            (-33.4489, -70.6693, [88, 26, 16, 236, 120, 184, 224, 213]),
+            // Sydney, south east
+            (-33.8688, 151.2093, [0, 8, 208, 235, 72, 181, 32, 90]),
+            // Reykjavik, north west
+            (64.1466, -21.9426, [144, 254, 59, 38, 48, 211, 235, 242]),
+            // Tallinn, north east
+            (59.4370, 24.7536, [208, 93, 109, 35, 128, 25, 193, 14]),
+            (90., 180., [0, 233, 164, 53, 0, 210, 73, 107]),
+            (-90., -180., [0, 23, 91, 202, 0, 46, 182, 148]),
+        ] {
+            let msg = RemoteIDMessage::System(system(lat, lon));
+            let data = encode_message(&msg, EncodeMode::Strict).unwrap();
+            assert_eq!(expected, data[2..10], "{lat}, {lon}");
+
+            let decoded = from_message_buffer(&data).unwrap();
+            assert_eq!(msg, decoded);
+
+            let RemoteIDMessage::System(decoded) = decoded else {
+                unreachable!()
+            };
+            assert_eq!(lat, decoded.operator_latidute.degrees());
+            assert_eq!(lon, decoded.operator_longitude.degrees());
+        }
+    }
+
+    #[test]
+    fn system_coordinates_out_of_bounds() {
+        let mut buffer = [0u8; 25];
+        buffer[0] = 0x42;
+        buffer[2..6].copy_from_slice(&900_000_001i32.to_le_bytes());
+        let msg = from_message_buffer(&buffer).unwrap();
+
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "latitude",
+                value: 90.0000001
+            }),
+            encode_message(&msg, EncodeMode::Strict)
+        );
+        assert_eq!([0; 4], to_service_data(&msg)[2..6]);
+    }
+
+    #[test]
+    fn reserved_values_round_trip() {
+        let mut basic_id = to_service_data(&RemoteIDMessage::BasicID(BasicId {
+            id_type: IdType::SerialNumber,
+            ua_type: UAType::None,
+            uas_id: copy_to_id("1596F359746167260749".as_bytes()),
+        }));
+        basic_id[1] = 0x9F;
+
+        let mut location = to_service_data(&RemoteIDMessage::Location(location()));
+        location[1] = put_bits!(0xCu8, 7..4) | (location[1] & 0xF);
+        location[19] = 0x7D;
+        location[20] = 0xF5;
+
+        let mut system = [0u8; 25];
+        system[0] = 0x42;
+        system[1] = put_bits!(5u8, 4..2) | 3;
+        system[17] = 0x4C;
+
+        let RemoteIDMessage::BasicID(msg) = from_message_buffer(&basic_id).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(IdType::Unknown(9), msg.id_type);
+        assert_eq!(UAType::Other, msg.ua_type);
+
+        let RemoteIDMessage::Location(msg) = from_message_buffer(&location).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(OperationalStatus::Unknown(0xC), msg.operational_status);
+        assert_eq!(VerticalAccuracy::Reserved(7), msg.vertical_accuracy);
+        assert_eq!(HorizontalAccuracy::Reserved(13), msg.horizontal_accuracy);
+        assert_eq!(VerticalAccuracy::Reserved(15), msg.baro_altitude_accuracy);
+        assert_eq!(SpeedAccuracy::Reserved(5), msg.speed_accuracy);
+
+        let RemoteIDMessage::System(msg) = from_message_buffer(&system).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(ClassificationType::Unknown(5), msg.classification_type);
+        assert_eq!(OperatorLocationType::Unknown(3), msg.operator_location_type);
+        assert_eq!(UaCategory::Unknown(4), msg.ua_classification.category);
+        assert_eq!(UaClass::Unknown(12), msg.ua_classification.class);
+
+        for data in [basic_id, location, system] {
+            let msg = from_message_buffer(&data).unwrap();
+            assert_eq!(Ok(data), encode_message(&msg, EncodeMode::Strict));
+        }
+    }
+
+    #[test]
+    fn reserved_values_out_of_range() {
+        let mut msg = location();
+        msg.speed_accuracy = SpeedAccuracy::Reserved(16);
+        let msg = RemoteIDMessage::Location(msg);
+
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "speed accuracy",
+                value: 16.
+            }),
+            encode_message(&msg, EncodeMode::Strict)
+        );
+        assert_eq!(0, to_service_data(&msg)[20] & 0xF);
+    }
+
+    #[test]
+    fn authentication_page_out_of_range() {
+        let page = |page: AuthPage| {
+            RemoteIDMessage::Authentication(Authentication {
+                auth_type: AuthType::UasIdSignature,
+                page,
+            })
+        };
+        let continuation = |page_number| {
+            page(AuthPage::Continuation {
+                page_number,
+                data: [0; PAGE_DATA_SIZE],
+            })
+        };
+
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "page number",
+                value: 16.
+            }),
+            encode_message(&continuation(16), EncodeMode::Strict)
+        );
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "page number",
+                value: 0.
+            }),
+            encode_message(&continuation(0), EncodeMode::Strict)
+        );
+        assert_eq!(0x1F, to_service_data(&continuation(16))[1]);
+
+        let first = page(AuthPage::First {
+            last_page_index: 16,
+            length: 0,
+            timestamp: DateTime::from_timestamp(TIMESTAMP_EPOCH_2019, 0).unwrap(),
+            data: [0; PAGE_ZERO_DATA_SIZE],
+        });
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "last page index",
+                value: 16.
+            }),
+            encode_message(&first, EncodeMode::Strict)
+        );
+        assert_eq!(15, to_service_data(&first)[2]);
+
+        // more data than page 0 holds
+        let first = page(AuthPage::First {
+            last_page_index: 0,
+            length: 18,
+            timestamp: DateTime::from_timestamp(TIMESTAMP_EPOCH_2019, 0).unwrap(),
+            data: [0; PAGE_ZERO_DATA_SIZE],
+        });
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "authentication length",
+                value: 18.
+            }),
+            encode_message(&first, EncodeMode::Strict)
+        );
+        let data = to_service_data(&first);
+        assert_eq!([0, 17], data[2..4]);
+        assert!(from_message_buffer(&data).is_ok());
+    }
+
+    #[test]
+    fn from_known() {
+        assert_eq!(Ok(IdType::UtmAssignedId), IdType::from_known(3));
+        assert_eq!(Err(UnknownValue(5)), IdType::from_known(5));
+        assert_eq!(Ok(UaClass::Class6), UaClass::from_known(7));
+        assert_eq!(Err(UnknownValue(8)), UaClass::from_known(8));
+        assert_eq!(Err(UnknownValue(13)), HorizontalAccuracy::from_known(13));
+        assert_eq!(
+            UnknownValue(201),
+            OperatorIdType::from_known(201).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn encode_system_versions() {
+        let msg = RemoteIDMessage::System(system());
+
+        let v20 = encode_versioned(&msg, ProtocolVersion::F3411_20, EncodeMode::Strict).unwrap();
+        assert_eq!(0x41, v20[0]);
+        assert_eq!(to_service_data(&msg)[1..], v20[1..]);
+        assert_eq!(
+            Ok((ProtocolVersion::F3411_20, msg.clone())),
+            decode_versioned(&v20)
+        );
+
+        assert_eq!(
+            Err(EncodeError::UnsupportedField {
+                field: "ua classification",
+                version: ProtocolVersion::F3411_19
+            }),
+            encode_versioned(&msg, ProtocolVersion::F3411_19, EncodeMode::Strict)
+        );
+
+        let v19 = encode_versioned(&msg, ProtocolVersion::F3411_19, EncodeMode::Clamp).unwrap();
+        let expected = [
+            64, 0, 128, 76, 186, 29, 200, 227, 79, 5, 1, 0, 25, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        assert_eq!(expected, v19);
+
+        let (version, decoded) = decode_versioned(&v19).unwrap();
+        assert_eq!(ProtocolVersion::F3411_19, version);
+        let RemoteIDMessage::System(ref system) = decoded else {
+            unreachable!()
+        };
+        assert_eq!(ClassificationType::Undeclared, system.classification_type);
+        assert_eq!(UaClassification::undefined(), system.ua_classification);
+        assert_eq!(-1000., system.operator_altitude);
+        assert_eq!(None, system.timestamp);
+
+        assert_eq!(
+            Ok(v19),
+            encode_versioned(&decoded, ProtocolVersion::F3411_19, EncodeMode::Strict)
+        );
+
+        // only F3411-19 messages go without a timestamp
+        assert_eq!(
+            Err(EncodeError::UnsupportedField {
+                field: "unknown timestamp",
+                version: ProtocolVersion::F3411_20
+            }),
+            encode_versioned(&decoded, ProtocolVersion::F3411_20, EncodeMode::Strict)
+        );
+        let v20 = encode_versioned(&decoded, ProtocolVersion::F3411_20, EncodeMode::Clamp).unwrap();
+        assert_eq!([0; 4], v20[20..24]);
+    }
+
+    #[test]
+    fn encode_location_versions() {
+        let mut msg = location();
+        msg.timestamp_accuracy = Some(core::time::Duration::from_millis(200));
+        let msg = RemoteIDMessage::Location(msg);
+
+        let v22 = encode_message(&msg, EncodeMode::Strict).unwrap();
+        assert_eq!(2, v22[23]);
+
+        assert_eq!(
+            Err(EncodeError::UnsupportedField {
+                field: "timestamp accuracy",
+                version: ProtocolVersion::F3411_19
+            }),
+            encode_versioned(&msg, ProtocolVersion::F3411_19, EncodeMode::Strict)
+        );
+
+        // the reserved byte is ignored even if a F3411-19 transmitter fills it
+        let mut v19 = v22;
+        v19[0] = 0x10;
+        let Ok((ProtocolVersion::F3411_19, RemoteIDMessage::Location(decoded))) =
+            decode_versioned(&v19)
+        else {
+            panic!("not a F3411-19 location");
+        };
+        assert_eq!(None, decoded.timestamp_accuracy);
+    }
+
+    #[test]
+    fn encode_message_pack_version() {
+        let msgs = [RemoteIDMessage::System(system())];
+        let mut buf = [0u8; 3 + 25];
+        let len = encode_message_pack_versioned(
+            &mut buf,
+            &msgs,
+            ProtocolVersion::F3411_20,
+            EncodeMode::Strict,
+        )
+        .unwrap();
+
+        let pack = decode_message_pack(&buf[..len]).unwrap();
+        assert_eq!(ProtocolVersion::F3411_20, pack.version());
+        assert_eq!(0x41, buf[3]);
+    }
 }
@@ -1,34 +1,77 @@
+use crate::data::{RemoteIDMessage, UnknownValue};
 use crate::MAX_ID_BYTE_SIZE;
 
+pub mod auth;
 pub mod decode;
 pub mod encode;
 
-#[repr(u8)]
-#[derive(Debug, Copy, Clone)]
-enum MessageType {
-    BasicId = 0,
-    Location = 1,
-    Auth = 2,
-    Selfid = 3,
-    System = 4,
-    OperatorId = 5,
-    MessagePack = 0xF,
-
-    Invalid,
+/// Size of a single encoded message
+pub const MESSAGE_SIZE: usize = 25;
+
+/// Maximum number of messages in a Message Pack
+pub const MAX_MESSAGES_IN_PACK: usize = 9;
+
+/// Largest Message Pack, its header followed by nine messages
+pub const MAX_MESSAGE_PACK_SIZE: usize = 3 + MAX_MESSAGES_IN_PACK * MESSAGE_SIZE;
+
+/// Seconds between the Unix epoch and 00:00:00 01/01/2019, the epoch of Remote ID timestamps
+pub(crate) const TIMESTAMP_EPOCH_2019: i64 = 1546300800;
+
+/// Kind of an encoded message, sent in the high nibble of its header
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MessageKind {
+    BasicID,
+    Location,
+    Authentication,
+    SelfId,
+    System,
+    OperatorId,
+    MessagePack,
 }
 
-impl From<u8> for MessageType {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for MessageKind {
+    type Error = UnknownValue;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        // 6-14: Reserved
         match value {
-            0 => MessageType::BasicId,
-            1 => MessageType::Location,
-            2 => MessageType::Auth,
-            3 => MessageType::Selfid,
-            4 => MessageType::System,
-            5 => MessageType::OperatorId,
-            0xF => MessageType::MessagePack,
-
-            _ => MessageType::Invalid,
+            0 => Ok(MessageKind::BasicID),
+            1 => Ok(MessageKind::Location),
+            2 => Ok(MessageKind::Authentication),
+            3 => Ok(MessageKind::SelfId),
+            4 => Ok(MessageKind::System),
+            5 => Ok(MessageKind::OperatorId),
+            0xF => Ok(MessageKind::MessagePack),
+
+            _ => Err(UnknownValue(value)),
+        }
+    }
+}
+
+impl From<MessageKind> for u8 {
+    fn from(value: MessageKind) -> Self {
+        match value {
+            MessageKind::BasicID => 0,
+            MessageKind::Location => 1,
+            MessageKind::Authentication => 2,
+            MessageKind::SelfId => 3,
+            MessageKind::System => 4,
+            MessageKind::OperatorId => 5,
+            MessageKind::MessagePack => 0xF,
+        }
+    }
+}
+
+impl RemoteIDMessage {
+    /// Kind of the message, as sent in its header
+    pub fn kind(&self) -> MessageKind {
+        match self {
+            RemoteIDMessage::BasicID(_) => MessageKind::BasicID,
+            RemoteIDMessage::Location(_) => MessageKind::Location,
+            RemoteIDMessage::Authentication(_) => MessageKind::Authentication,
+            RemoteIDMessage::System(_) => MessageKind::System,
+            RemoteIDMessage::OperatorId(_) => MessageKind::OperatorId,
+            RemoteIDMessage::SelfId(_) => MessageKind::SelfId,
         }
     }
 }
@@ -49,12 +92,16 @@ macro_rules! bitmask {
     ($lo:literal, $hi:literal) => {{
         let mut pattern = 1;
 
-        for _ in 0..($hi - $lo) {
+        let mut i = $lo;
+        while i < $hi {
             pattern = (pattern << 1) + 1;
+            i += 1;
         }
 
-        for _ in 0..$lo {
-            pattern = (pattern << 1);
+        let mut i = 0;
+        while i < $lo {
+            pattern <<= 1;
+            i += 1;
         }
 
         pattern
@@ -96,16 +143,20 @@ macro_rules! get_bytes {
 #[macro_export]
 macro_rules! get_bits {
     ($num:expr, $hi:literal..$lo:literal) => {{
-        let p = crate::bitmask!($lo, $hi);
+        let p = $crate::bitmask!($lo, $hi);
         ($num & p) >> $lo
     }};
 }
 
+/// Place a value into the given bits
+/// ```ignore
+/// assert_eq!(put_bits!(0b11, 5..4), 0b0011_0000)
+/// ```  
 #[macro_export]
 macro_rules! put_bits {
     ($num:expr, $hi:literal..$lo:literal) => {{
-        let p = crate::bitmask!($lo, $hi);
-        ($num & p) >> $lo
+        let p = $crate::bitmask!($lo, $hi);
+        ($num << $lo) & p
     }};
 }
 
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+
+pub const MESSAGE_TYPE: u8 = 0x2;
+
+/// Maximum number of pages a single authentication payload can be split into
+pub const MAX_PAGES: usize = 16;
+
+/// Authentication data carried on page 0
+pub const PAGE_ZERO_DATA_SIZE: usize = 17;
+
+/// Authentication data carried on pages 1..N
+pub const PAGE_DATA_SIZE: usize = 23;
+
+/// The length field is a single byte, so a payload is at most 255 bytes long
+pub const MAX_DATA_SIZE: usize = u8::MAX as usize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Authentication {
+    pub auth_type: AuthType,
+    pub page: AuthPage,
+}
+
+impl Authentication {
+    pub fn page_number(&self) -> u8 {
+        match self.page {
+            AuthPage::First { .. } => 0,
+            AuthPage::Continuation { page_number, .. } => page_number,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthPage {
+    /// Page 0, describing the whole payload
+    First {
+        /// Index of the last page, i.e. the number of pages - 1
+        last_page_index: u8,
+        /// Total bytes of authentication data over all pages
+        length: u8,
+        /// Seconds since 00:00:00 01/01/2019
+        timestamp: DateTime<Utc>,
+        data: [u8; PAGE_ZERO_DATA_SIZE],
+    },
+
+    /// Pages 1..N
+    Continuation {
+        page_number: u8,
+        data: [u8; PAGE_DATA_SIZE],
+    },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AuthType {
+    None,
+    UasIdSignature,
+    OperatorIdSignature,
+    MessageSetSignature,
+    /// Authentication provided by Network Remote ID
+    NetworkRemoteId,
+    SpecificAuthenticationMethod,
+
+    /// 6-9: Reserved, 0xA-0xF: Available for private use
+    Unknown(u8),
+}
+
+impl From<u8> for AuthType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => AuthType::None,
+            1 => AuthType::UasIdSignature,
+            2 => AuthType::OperatorIdSignature,
+            3 => AuthType::MessageSetSignature,
+            4 => AuthType::NetworkRemoteId,
+            5 => AuthType::SpecificAuthenticationMethod,
+
+            _ => AuthType::Unknown(value),
+        }
+    }
+}
+
+from_known!(AuthType, Unknown);
+
+impl From<AuthType> for u8 {
+    fn from(value: AuthType) -> Self {
+        match value {
+            AuthType::None => 0,
+            AuthType::UasIdSignature => 1,
+            AuthType::OperatorIdSignature => 2,
+            AuthType::MessageSetSignature => 3,
+            AuthType::NetworkRemoteId => 4,
+            AuthType::SpecificAuthenticationMethod => 5,
+            AuthType::Unknown(value) => value,
+        }
+    }
+}
+
+/// A complete authentication payload, reassembled from all of its pages
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthPayload {
+    pub auth_type: AuthType,
+    pub timestamp: DateTime<Utc>,
+    length: u8,
+    buffer: [u8; MAX_DATA_SIZE],
+}
+
+impl AuthPayload {
+    pub fn new(auth_type: AuthType, timestamp: DateTime<Utc>, data: &[u8]) -> Option<Self> {
+        if data.len() > MAX_DATA_SIZE {
+            return None;
+        }
+
+        let mut buffer = [0u8; MAX_DATA_SIZE];
+        buffer[..data.len()].copy_from_slice(data);
+
+        Some(Self {
+            auth_type,
+            timestamp,
+            length: data.len() as u8,
+            buffer,
+        })
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.buffer[..self.length as usize]
+    }
+}
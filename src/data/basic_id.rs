@@ -1,22 +1,490 @@
+use core::fmt::{Display, Formatter, Write};
+
 use crate::MAX_ID_BYTE_SIZE;
 
 pub const MESSAGE_TYPE: u8 = 0;
 
+/// Size of a UTM-assigned UUID, the rest of the UAS ID is zeroed
+pub const UUID_SIZE: usize = 16;
+
+/// Size of a Specific Session ID after its session ID type
+pub const SESSION_ID_SIZE: usize = MAX_ID_BYTE_SIZE - 1;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct BasicId {
     pub id_type: IdType,
     pub ua_type: UAType,
+    /// Raw UAS ID as sent, its meaning depends on `id_type`, see [`BasicId::typed_uas_id`]
     pub uas_id: [u8; MAX_ID_BYTE_SIZE],
 }
 
+impl BasicId {
+    pub fn new(ua_type: UAType, uas_id: &UasId) -> Self {
+        Self {
+            id_type: uas_id.id_type(),
+            ua_type,
+            uas_id: uas_id.to_bytes(),
+        }
+    }
+
+    /// The UAS ID interpreted according to the ID type
+    pub fn typed_uas_id(&self) -> Result<UasId, UasIdError> {
+        UasId::parse(self.id_type, &self.uas_id)
+    }
+}
+
+/// UAS ID of a Basic ID message, interpreted according to its [`IdType`].
+///
+/// `Display` writes the text form read by [`UasId::parse_str`], nothing for [`UasId::None`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum UasId {
+    /// No UAS ID, the field is zeroed
+    None,
+    /// ANSI/CTA-2063-A serial number of the UA
+    SerialNumber(IdText),
+    /// Registration ID issued by a Civil Aviation Authority, e.g. "N.12345"
+    CaaRegistrationId(IdText),
+    /// UUID assigned by a UTM service provider for the flight
+    UtmAssignedId([u8; UUID_SIZE]),
+    /// ID of the session, in a format given by the session ID type.
+    /// 1: IETF DRIP, 2: IEEE 1609.2-2016 HashedId8, 224-255: private use
+    SpecificSessionId {
+        session_id_type: u8,
+        id: [u8; SESSION_ID_SIZE],
+    },
+}
+
+/// Reasons a UAS ID is not valid for its ID type
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UasIdError {
+    /// The ID type is reserved and has no defined format
+    UnknownIdType(u8),
+    /// Text that is empty, longer than 20 characters or holds other than printable ASCII
+    InvalidText,
+    /// The bytes after the ID are not zeroed
+    NonZeroPadding,
+    /// A session ID type that is reserved
+    ReservedSessionIdType(u8),
+    /// Text that is not the hex form of the ID
+    InvalidHex,
+    /// A serial number that does not follow ANSI/CTA-2063-A
+    InvalidSerialNumber(SerialNumberError),
+}
+
+impl Display for UasIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            UasIdError::UnknownIdType(t) => write!(f, "unknown ID type: {t}"),
+            UasIdError::InvalidText => write!(f, "invalid ID text"),
+            UasIdError::NonZeroPadding => write!(f, "padding after the ID is not zeroed"),
+            UasIdError::ReservedSessionIdType(t) => write!(f, "reserved session ID type: {t}"),
+            UasIdError::InvalidHex => write!(f, "invalid hex ID"),
+            UasIdError::InvalidSerialNumber(e) => write!(f, "invalid serial number: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for UasIdError {}
+
+impl UasId {
+    /// Interpret the raw UAS ID of a Basic ID message
+    pub fn parse(id_type: IdType, raw: &[u8; MAX_ID_BYTE_SIZE]) -> Result<Self, UasIdError> {
+        match id_type {
+            IdType::None => {
+                ensure_zeroed(raw)?;
+                Ok(UasId::None)
+            }
+            IdType::SerialNumber => IdText::from_bytes(raw).map(UasId::SerialNumber),
+            IdType::CaaRegistrationId => IdText::from_bytes(raw).map(UasId::CaaRegistrationId),
+            IdType::UtmAssignedId => {
+                let (uuid, padding) = raw.split_at(UUID_SIZE);
+                ensure_zeroed(padding)?;
+                Ok(UasId::UtmAssignedId(uuid.try_into().unwrap()))
+            }
+            IdType::SpecificSessionId => {
+                let session_id_type = raw[0];
+                ensure_session_id_type(session_id_type)?;
+                Ok(UasId::SpecificSessionId {
+                    session_id_type,
+                    id: raw[1..].try_into().unwrap(),
+                })
+            }
+            IdType::Unknown(value) => Err(UasIdError::UnknownIdType(value)),
+        }
+    }
+
+    /// Parse the text form of a UAS ID, as written by `Display`.
+    ///
+    /// UTM-assigned IDs are written as a UUID, Specific Session IDs as the session ID type and
+    /// the hex ID separated by a colon, e.g. `1:2001003ffe...`.
+    pub fn parse_str(id_type: IdType, text: &str) -> Result<Self, UasIdError> {
+        match id_type {
+            IdType::None if text.is_empty() => Ok(UasId::None),
+            IdType::None => Err(UasIdError::InvalidText),
+            IdType::SerialNumber => IdText::new(text).map(UasId::SerialNumber),
+            IdType::CaaRegistrationId => IdText::new(text).map(UasId::CaaRegistrationId),
+            IdType::UtmAssignedId => {
+                let mut uuid = [0u8; UUID_SIZE];
+                // 8-4-4-4-12 hex digits
+                let mut groups = text.split('-');
+                let mut offset = 0;
+                for len in [4, 2, 2, 2, 6] {
+                    let group = groups.next().ok_or(UasIdError::InvalidHex)?;
+                    parse_hex(group, &mut uuid[offset..(offset + len)])?;
+                    offset += len;
+                }
+                if groups.next().is_some() {
+                    return Err(UasIdError::InvalidHex);
+                }
+
+                Ok(UasId::UtmAssignedId(uuid))
+            }
+            IdType::SpecificSessionId => {
+                let (session_id_type, hex) = text.split_once(':').ok_or(UasIdError::InvalidHex)?;
+                let session_id_type = session_id_type
+                    .parse()
+                    .map_err(|_| UasIdError::InvalidText)?;
+                ensure_session_id_type(session_id_type)?;
+
+                let mut id = [0u8; SESSION_ID_SIZE];
+                parse_hex(hex, &mut id)?;
+                Ok(UasId::SpecificSessionId {
+                    session_id_type,
+                    id,
+                })
+            }
+            IdType::Unknown(value) => Err(UasIdError::UnknownIdType(value)),
+        }
+    }
+
+    pub fn id_type(&self) -> IdType {
+        match self {
+            UasId::None => IdType::None,
+            UasId::SerialNumber(_) => IdType::SerialNumber,
+            UasId::CaaRegistrationId(_) => IdType::CaaRegistrationId,
+            UasId::UtmAssignedId(_) => IdType::UtmAssignedId,
+            UasId::SpecificSessionId { .. } => IdType::SpecificSessionId,
+        }
+    }
+
+    /// The raw UAS ID as sent in a Basic ID message
+    pub fn to_bytes(&self) -> [u8; MAX_ID_BYTE_SIZE] {
+        let mut raw = [0u8; MAX_ID_BYTE_SIZE];
+        match self {
+            UasId::None => {}
+            UasId::SerialNumber(text) | UasId::CaaRegistrationId(text) => {
+                raw = *text.as_bytes();
+            }
+            UasId::UtmAssignedId(uuid) => raw[..UUID_SIZE].copy_from_slice(uuid),
+            UasId::SpecificSessionId {
+                session_id_type,
+                id,
+            } => {
+                raw[0] = *session_id_type;
+                raw[1..].copy_from_slice(id);
+            }
+        }
+        raw
+    }
+}
+
+impl Display for UasId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            UasId::None => Ok(()),
+            UasId::SerialNumber(text) | UasId::CaaRegistrationId(text) => text.fmt(f),
+            UasId::UtmAssignedId(uuid) => {
+                for (i, byte) in uuid.iter().enumerate() {
+                    if matches!(i, 4 | 6 | 8 | 10) {
+                        f.write_char('-')?;
+                    }
+                    write!(f, "{byte:02x}")?;
+                }
+                Ok(())
+            }
+            UasId::SpecificSessionId {
+                session_id_type,
+                id,
+            } => {
+                write!(f, "{session_id_type}:")?;
+                id.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+            }
+        }
+    }
+}
+
+/// A UAS ID sent as printable ASCII text, padded with NULs to 20 bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdText([u8; MAX_ID_BYTE_SIZE]);
+
+impl IdText {
+    pub fn new(text: &str) -> Result<Self, UasIdError> {
+        if text.len() > MAX_ID_BYTE_SIZE {
+            return Err(UasIdError::InvalidText);
+        }
+
+        let mut raw = [0u8; MAX_ID_BYTE_SIZE];
+        raw[..text.len()].copy_from_slice(text.as_bytes());
+        Self::from_bytes(&raw)
+    }
+
+    /// Validate the raw bytes of an ID text
+    pub fn from_bytes(raw: &[u8; MAX_ID_BYTE_SIZE]) -> Result<Self, UasIdError> {
+        let len = raw.iter().position(|&b| b == 0).unwrap_or(MAX_ID_BYTE_SIZE);
+        let (text, padding) = raw.split_at(len);
+
+        if text.is_empty() || !text.iter().all(u8::is_ascii_graphic) {
+            return Err(UasIdError::InvalidText);
+        }
+        ensure_zeroed(padding)?;
+
+        Ok(Self(*raw))
+    }
+
+    pub fn as_str(&self) -> &str {
+        let len = self
+            .0
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(MAX_ID_BYTE_SIZE);
+        // validated to be ASCII on construction
+        core::str::from_utf8(&self.0[..len]).unwrap()
+    }
+
+    pub fn as_bytes(&self) -> &[u8; MAX_ID_BYTE_SIZE] {
+        &self.0
+    }
+}
+
+impl Display for IdText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Size of the manufacturer code at the start of a serial number
+pub const MANUFACTURER_CODE_SIZE: usize = 4;
+
+/// Longest manufacturer serial, announced by the length code F
+pub const MAX_MANUFACTURER_SERIAL_SIZE: usize = 15;
+
+/// ANSI/CTA-2063-A serial number, e.g. "1596F359746167260749": the manufacturer code assigned
+/// by ICAO, a length code and the serial assigned by the manufacturer.
+///
+/// The length code is a hex digit 1-F giving the length of the serial. All other characters
+/// are digits and upper case letters except O and I.
+/// ```
+/// # use remote_id::data::basic_id::SerialNumber;
+/// let serial = SerialNumber::new("1596", "359746167260749").unwrap();
+/// assert_eq!("1596F359746167260749", serial.as_str());
+/// assert_eq!(serial, SerialNumber::parse("1596F359746167260749").unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerialNumber(IdText);
+
+/// Reasons a text is not an ANSI/CTA-2063-A serial number
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SerialNumberError {
+    /// A character other than a digit or an upper case letter, or one of O and I. The
+    /// position is counted from the start of the serial number.
+    InvalidCharacter { position: usize },
+    /// The manufacturer code is not 4 characters long
+    InvalidManufacturerCode,
+    /// The text ends before the length code
+    MissingLengthCode,
+    /// A length code other than 1-9 and A-F
+    InvalidLengthCode(char),
+    /// The manufacturer serial is empty or longer than 15 characters
+    InvalidSerialLength(usize),
+    /// The manufacturer serial is not as long as the length code says
+    LengthMismatch { expected: usize, actual: usize },
+}
+
+impl Display for SerialNumberError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SerialNumberError::InvalidCharacter { position } => {
+                write!(f, "invalid character at position {position}")
+            }
+            SerialNumberError::InvalidManufacturerCode => write!(f, "invalid manufacturer code"),
+            SerialNumberError::MissingLengthCode => write!(f, "missing length code"),
+            SerialNumberError::InvalidLengthCode(c) => write!(f, "invalid length code: {c:?}"),
+            SerialNumberError::InvalidSerialLength(len) => {
+                write!(f, "invalid manufacturer serial length: {len}")
+            }
+            SerialNumberError::LengthMismatch { expected, actual } => write!(
+                f,
+                "length code announces {expected} characters, manufacturer serial has {actual}"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for SerialNumberError {}
+
+impl From<SerialNumberError> for UasIdError {
+    fn from(e: SerialNumberError) -> Self {
+        UasIdError::InvalidSerialNumber(e)
+    }
+}
+
+impl SerialNumber {
+    /// Build the serial number of a manufacturer code and serial, adding the length code
+    pub fn new(manufacturer_code: &str, serial: &str) -> Result<Self, SerialNumberError> {
+        if manufacturer_code.len() != MANUFACTURER_CODE_SIZE {
+            return Err(SerialNumberError::InvalidManufacturerCode);
+        }
+        if serial.is_empty() || serial.len() > MAX_MANUFACTURER_SERIAL_SIZE {
+            return Err(SerialNumberError::InvalidSerialLength(serial.len()));
+        }
+
+        let mut raw = [0u8; MAX_ID_BYTE_SIZE];
+        let (code, rest) = raw.split_at_mut(MANUFACTURER_CODE_SIZE);
+        code.copy_from_slice(manufacturer_code.as_bytes());
+        // 1-15 as a single upper case hex digit
+        rest[0] = b"0123456789ABCDEF"[serial.len()];
+        rest[1..=serial.len()].copy_from_slice(serial.as_bytes());
+
+        Self::from_bytes(&raw)
+    }
+
+    /// Parse and validate the text form of a serial number
+    pub fn parse(text: &str) -> Result<Self, SerialNumberError> {
+        if text.len() > MAX_ID_BYTE_SIZE {
+            return Err(SerialNumberError::InvalidSerialLength(
+                text.len().saturating_sub(MANUFACTURER_CODE_SIZE + 1),
+            ));
+        }
+
+        let mut raw = [0u8; MAX_ID_BYTE_SIZE];
+        raw[..text.len()].copy_from_slice(text.as_bytes());
+        Self::from_bytes(&raw)
+    }
+
+    /// Validate the raw UAS ID of a Basic ID message
+    pub fn from_bytes(raw: &[u8; MAX_ID_BYTE_SIZE]) -> Result<Self, SerialNumberError> {
+        let len = raw.iter().position(|&b| b == 0).unwrap_or(MAX_ID_BYTE_SIZE);
+        let (text, padding) = raw.split_at(len);
+
+        if let Some(position) = text.iter().position(|&b| !is_serial_char(b)) {
+            return Err(SerialNumberError::InvalidCharacter { position });
+        }
+        // a NUL within the text
+        if padding.iter().any(|&b| b != 0) {
+            return Err(SerialNumberError::InvalidCharacter { position: len });
+        }
+
+        if len < MANUFACTURER_CODE_SIZE {
+            return Err(SerialNumberError::InvalidManufacturerCode);
+        }
+        let length_code = *text
+            .get(MANUFACTURER_CODE_SIZE)
+            .ok_or(SerialNumberError::MissingLengthCode)?;
+        let expected = match length_code {
+            b'1'..=b'9' => (length_code - b'0') as usize,
+            b'A'..=b'F' => (length_code - b'A') as usize + 10,
+            _ => return Err(SerialNumberError::InvalidLengthCode(length_code as char)),
+        };
+
+        let actual = len - MANUFACTURER_CODE_SIZE - 1;
+        if actual != expected {
+            return Err(SerialNumberError::LengthMismatch { expected, actual });
+        }
+
+        // the checks above leave only ASCII digits and letters
+        Ok(Self(IdText(*raw)))
+    }
+
+    /// The manufacturer code assigned by ICAO
+    pub fn manufacturer_code(&self) -> &str {
+        &self.as_str()[..MANUFACTURER_CODE_SIZE]
+    }
+
+    /// The length code, a hex digit 1-F
+    pub fn length_code(&self) -> char {
+        self.0 .0[MANUFACTURER_CODE_SIZE] as char
+    }
+
+    /// The serial assigned by the manufacturer
+    pub fn manufacturer_serial(&self) -> &str {
+        &self.as_str()[MANUFACTURER_CODE_SIZE + 1..]
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn as_id_text(&self) -> &IdText {
+        &self.0
+    }
+}
+
+impl TryFrom<&IdText> for SerialNumber {
+    type Error = SerialNumberError;
+
+    fn try_from(text: &IdText) -> Result<Self, Self::Error> {
+        Self::from_bytes(text.as_bytes())
+    }
+}
+
+impl From<SerialNumber> for UasId {
+    fn from(serial: SerialNumber) -> Self {
+        UasId::SerialNumber(serial.0)
+    }
+}
+
+impl Display for SerialNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Digits and upper case letters, O and I are left out to not be confused with 0 and 1
+fn is_serial_char(b: u8) -> bool {
+    (b.is_ascii_digit() || b.is_ascii_uppercase()) && b != b'O' && b != b'I'
+}
+
+fn ensure_zeroed(bytes: &[u8]) -> Result<(), UasIdError> {
+    if bytes.iter().any(|&b| b != 0) {
+        return Err(UasIdError::NonZeroPadding);
+    }
+
+    Ok(())
+}
+
+fn ensure_session_id_type(session_id_type: u8) -> Result<(), UasIdError> {
+    // 0 and 3-223: Reserved
+    match session_id_type {
+        1 | 2 | 224.. => Ok(()),
+        _ => Err(UasIdError::ReservedSessionIdType(session_id_type)),
+    }
+}
+
+/// Parse exactly `out.len()` bytes written as hex digits
+fn parse_hex(text: &str, out: &mut [u8]) -> Result<(), UasIdError> {
+    if text.len() != 2 * out.len() || !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(UasIdError::InvalidHex);
+    }
+
+    for (byte, digits) in out.iter_mut().zip(text.as_bytes().chunks_exact(2)) {
+        let digits = core::str::from_utf8(digits).unwrap();
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| UasIdError::InvalidHex)?;
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum IdType {
-    None = 0,
+    None,
     /// ANSI/CTA-2063-A
-    SerialNumber = 1,
-    CaaRegistrationId = 2,
-    UtmAssignedId = 3,
-    SpecificSessionId = 4,
+    SerialNumber,
+    CaaRegistrationId,
+    UtmAssignedId,
+    SpecificSessionId,
+
+    /// 5-15: Reserved
+    Unknown(u8),
 }
 
 impl From<u8> for IdType {
@@ -28,41 +496,47 @@ impl From<u8> for IdType {
             3 => IdType::UtmAssignedId,
             4 => IdType::SpecificSessionId,
 
-            _ => IdType::None,
+            _ => IdType::Unknown(value),
         }
     }
 }
 
-impl Into<u8> for IdType {
-    fn into(self) -> u8 {
-        match self {
+from_known!(IdType, Unknown);
+
+impl From<IdType> for u8 {
+    fn from(value: IdType) -> Self {
+        match value {
             IdType::None => 0,
             IdType::SerialNumber => 1,
             IdType::CaaRegistrationId => 2,
             IdType::UtmAssignedId => 3,
             IdType::SpecificSessionId => 4,
+            IdType::Unknown(value) => value,
         }
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum UAType {
-    None = 0,
-    Aeroplane = 1,
-    HelicopterOrMultirotor = 2,
-    Gyroplane = 3,
-    HybridLift = 4,
-    Ornithopter = 5,
-    Glider = 6,
-    Kite = 7,
-    FreeBalloon = 8,
-    CaptiveBalloon = 9,
-    Airship = 10,
-    FreeFallParachute = 11,
-    Rocket = 12,
-    TetheredPoweredAircraft = 13,
-    GroundObstacle = 14,
-    Other = 15,
+    None,
+    Aeroplane,
+    HelicopterOrMultirotor,
+    Gyroplane,
+    HybridLift,
+    Ornithopter,
+    Glider,
+    Kite,
+    FreeBalloon,
+    CaptiveBalloon,
+    Airship,
+    FreeFallParachute,
+    Rocket,
+    TetheredPoweredAircraft,
+    GroundObstacle,
+    Other,
+
+    /// Values that do not fit the 4 bit field
+    Unknown(u8),
 }
 
 impl From<u8> for UAType {
@@ -83,14 +557,18 @@ impl From<u8> for UAType {
             12 => UAType::Rocket,
             13 => UAType::TetheredPoweredAircraft,
             14 => UAType::GroundObstacle,
-            _ => UAType::Other,
+            15 => UAType::Other,
+
+            _ => UAType::Unknown(value),
         }
     }
 }
 
-impl Into<u8> for UAType {
-    fn into(self) -> u8 {
-        match self {
+from_known!(UAType, Unknown);
+
+impl From<UAType> for u8 {
+    fn from(value: UAType) -> Self {
+        match value {
             UAType::Aeroplane => 1,
             UAType::HelicopterOrMultirotor => 2,
             UAType::Gyroplane => 3,
@@ -108,6 +586,135 @@ impl Into<u8> for UAType {
             UAType::Other => 15,
 
             UAType::None => 0,
+            UAType::Unknown(value) => value,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+
+    #[test]
+    fn typed_uas_id() {
+        // DroneTag Mini
+        let basic_id = BasicId {
+            id_type: IdType::SerialNumber,
+            ua_type: UAType::None,
+            uas_id: *b"10000000000000000009",
+        };
+        let uas_id = basic_id.typed_uas_id().unwrap();
+        assert_eq!(
+            UasId::SerialNumber(IdText::new("10000000000000000009").unwrap()),
+            uas_id
+        );
+        assert_eq!("10000000000000000009", std::format!("{uas_id}"));
+        assert_eq!(basic_id, BasicId::new(UAType::None, &uas_id));
+
+        let uuid = "0b3d7d8e-5a9f-4c1e-9d2b-7e1f3a5c6b4d";
+        let uas_id = UasId::parse_str(IdType::UtmAssignedId, uuid).unwrap();
+        assert_eq!(uuid, std::format!("{uas_id}"));
+        assert_eq!([0x0B, 0x3D], uas_id.to_bytes()[..2]);
+        assert_eq!([0; 4], uas_id.to_bytes()[16..]);
+
+        let session_id = "1:2001003ffe3f00000000000000000000000000";
+        let uas_id = UasId::parse_str(IdType::SpecificSessionId, session_id).unwrap();
+        assert_eq!(session_id, std::format!("{uas_id}"));
+        assert_eq!(
+            Ok(uas_id.clone()),
+            UasId::parse(IdType::SpecificSessionId, &uas_id.to_bytes())
+        );
+
+        let caa = UasId::parse_str(IdType::CaaRegistrationId, "N.12345").unwrap();
+        assert_eq!(
+            Ok(caa.clone()),
+            UasId::parse(IdType::CaaRegistrationId, &caa.to_bytes())
+        );
+        assert_eq!(Ok(UasId::None), UasId::parse(IdType::None, &[0; 20]));
+
+        // NUL within the text, junk after the UUID, reserved types
+        let mut raw = *b"1596F359746167260749";
+        raw[4] = 0;
+        assert_eq!(
+            Err(UasIdError::NonZeroPadding),
+            UasId::parse(IdType::SerialNumber, &raw)
+        );
+        assert_eq!(
+            Err(UasIdError::NonZeroPadding),
+            UasId::parse(IdType::UtmAssignedId, &[1; 20])
+        );
+        assert_eq!(
+            Err(UasIdError::ReservedSessionIdType(0)),
+            UasId::parse(IdType::SpecificSessionId, &[0; 20])
+        );
+        assert_eq!(
+            Err(UasIdError::UnknownIdType(7)),
+            UasId::parse(IdType::Unknown(7), &[0; 20])
+        );
+        assert_eq!(
+            Err(UasIdError::InvalidText),
+            UasId::parse(IdType::SerialNumber, &[0; 20])
+        );
+        assert_eq!(Err(UasIdError::InvalidText), IdText::new("with space"));
+        assert_eq!(
+            Err(UasIdError::InvalidHex),
+            UasId::parse_str(
+                IdType::UtmAssignedId,
+                "+b3d7d8e-5a9f-4c1e-9d2b-7e1f3a5c6b4d"
+            )
+        );
+    }
+
+    #[test]
+    fn serial_number() {
+        let serial = SerialNumber::new("1596", "359746167260749").unwrap();
+        assert_eq!("1596", serial.manufacturer_code());
+        assert_eq!('F', serial.length_code());
+        assert_eq!("359746167260749", serial.manufacturer_serial());
+        assert_eq!(
+            Ok(serial.clone()),
+            SerialNumber::parse("1596F359746167260749")
+        );
+        assert_eq!("MFR12XY", SerialNumber::new("MFR1", "XY").unwrap().as_str());
+
+        assert_eq!(
+            Err(SerialNumberError::InvalidManufacturerCode),
+            SerialNumber::new("159", "1")
+        );
+        assert_eq!(
+            Err(SerialNumberError::InvalidSerialLength(16)),
+            SerialNumber::new("1596", "3597461672607491")
+        );
+        assert_eq!(
+            Err(SerialNumberError::InvalidCharacter { position: 6 }),
+            SerialNumber::parse("15962AO")
+        );
+        assert_eq!(
+            Err(SerialNumberError::InvalidCharacter { position: 1 }),
+            SerialNumber::parse("1a962AB")
+        );
+        assert_eq!(
+            Err(SerialNumberError::MissingLengthCode),
+            SerialNumber::parse("1596")
+        );
+        assert_eq!(
+            Err(SerialNumberError::InvalidLengthCode('G')),
+            SerialNumber::parse("1596G1")
+        );
+        assert_eq!(
+            Err(SerialNumberError::LengthMismatch {
+                expected: 2,
+                actual: 3
+            }),
+            SerialNumber::parse("15962ABC")
+        );
+        let mut raw = *serial.as_id_text().as_bytes();
+        raw[10] = 0;
+        assert_eq!(
+            Err(SerialNumberError::InvalidCharacter { position: 10 }),
+            SerialNumber::from_bytes(&raw)
+        );
+    }
+}
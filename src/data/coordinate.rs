@@ -0,0 +1,40 @@
+/// Wire resolution of latitudes and longitudes, 1e-7 degrees
+const SCALE: f64 = 1e7;
+
+/// A latitude or longitude as it is sent on the wire, in units of 1e-7 degrees.
+///
+/// Storing the wire integer keeps decode → encode bit-exact.
+/// ```
+/// # use remote_id::data::coordinate::Coordinate;
+/// let lat = Coordinate::from_degrees(49.8748559);
+/// assert_eq!(498748559, lat.raw());
+/// assert_eq!(49.8748559, lat.degrees());
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coordinate(i32);
+
+impl Coordinate {
+    pub const fn from_raw(raw: i32) -> Self {
+        Self(raw)
+    }
+
+    /// Round to the closest representable coordinate, saturating at the `i32` limits
+    pub fn from_degrees(degrees: f64) -> Self {
+        let scaled = degrees * SCALE;
+        let rounded = if scaled >= 0. {
+            scaled + 0.5
+        } else {
+            scaled - 0.5
+        };
+
+        Self(rounded as i32)
+    }
+
+    pub const fn raw(self) -> i32 {
+        self.0
+    }
+
+    pub fn degrees(self) -> f64 {
+        self.0 as f64 / SCALE
+    }
+}
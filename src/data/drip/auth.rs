@@ -0,0 +1,575 @@
+//! DRIP authentication (RFC 9575), sent as the Specific Authentication Method payload of
+//! Authentication messages.
+//!
+//! Each structure is the SAM type, the validity period, the evidence, the DET of the signer
+//! and an Ed25519 signature over the validity period, evidence and DET. With the host identity
+//! of the signer a receiver can check a broadcast without any network lookup: the DET must be
+//! derived from the host identity and the signature must be made with it.
+
+use core::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Utc};
+
+use super::hhit::{Hhit, HhitError, HHIT_SIZE};
+use crate::codec::decode::{from_message_buffer, DecodeError};
+use crate::codec::{MESSAGE_SIZE, TIMESTAMP_EPOCH_2019};
+use crate::data::authentication::{AuthPayload, AuthType, MAX_DATA_SIZE};
+use crate::data::RemoteIDMessage;
+
+/// Size of an Ed25519 signature
+pub const SIGNATURE_SIZE: usize = 64;
+
+/// Size of an Ed25519 host identity, the public key
+pub const HOST_IDENTITY_SIZE: usize = 32;
+
+/// Size of a Link's evidence: the DET and host identity of the endorsed UA
+pub const ENDORSEMENT_SIZE: usize = HHIT_SIZE + HOST_IDENTITY_SIZE;
+
+/// Most Remote ID messages a Wrapper carries
+pub const MAX_WRAPPED_MESSAGES: usize = 4;
+
+/// Size of a hash in a Manifest
+pub const MANIFEST_HASH_SIZE: usize = 8;
+
+/// SAM type, validity period, DET of the signer and signature around the evidence
+const OVERHEAD: usize = 1 + 4 + 4 + HHIT_SIZE + SIGNATURE_SIZE;
+
+/// Most evidence that fits into an authentication payload
+pub const MAX_EVIDENCE_SIZE: usize = MAX_DATA_SIZE - OVERHEAD;
+
+/// A DRIP authentication structure.
+///
+/// The evidence is checked to fit the SAM type on construction, the accessors of the SAM
+/// types read it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DripAuth<'a> {
+    sam_type: SamType,
+    valid_not_before: DateTime<Utc>,
+    valid_not_after: DateTime<Utc>,
+    evidence: &'a [u8],
+    /// DET of the entity that signed the structure
+    pub signer: Hhit,
+    pub signature: [u8; SIGNATURE_SIZE],
+}
+
+/// Reasons a DRIP authentication structure could not be read, built or verified
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DripAuthError {
+    /// The payload is not a Specific Authentication Method
+    WrongAuthType(AuthType),
+    /// The SAM type is not a DRIP one
+    UnknownSamType(u8),
+    /// The payload is shorter than the structure around the evidence
+    Truncated(usize),
+    /// The evidence does not fit into an authentication payload
+    EvidenceTooLong(usize),
+    /// The size of the evidence does not match the SAM type, or the endorsed DET is invalid
+    InvalidEvidence(SamType),
+    /// The DET of the signer is not an HHIT
+    InvalidSigner(HhitError),
+    /// A validity timestamp before 2019 or too far in the future
+    InvalidTimestamp,
+    /// The host identity does not derive to the DET of the signer
+    HostIdentityMismatch,
+    /// The host identity is not an Ed25519 public key
+    InvalidHostIdentity,
+    /// The signature was not made with the host identity
+    InvalidSignature,
+}
+
+impl Display for DripAuthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DripAuthError::WrongAuthType(t) => write!(f, "wrong authentication type: {t:?}"),
+            DripAuthError::UnknownSamType(t) => write!(f, "unknown SAM type: {t:#04x}"),
+            DripAuthError::Truncated(len) => write!(f, "truncated payload: {len} bytes"),
+            DripAuthError::EvidenceTooLong(len) => write!(f, "evidence too long: {len} bytes"),
+            DripAuthError::InvalidEvidence(t) => write!(f, "invalid evidence for {t:?}"),
+            DripAuthError::InvalidSigner(e) => write!(f, "invalid signer: {e}"),
+            DripAuthError::InvalidTimestamp => write!(f, "invalid timestamp"),
+            DripAuthError::HostIdentityMismatch => {
+                write!(f, "host identity does not match the signer")
+            }
+            DripAuthError::InvalidHostIdentity => write!(f, "invalid host identity"),
+            DripAuthError::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+impl core::error::Error for DripAuthError {}
+
+impl<'a> DripAuth<'a> {
+    /// Build a structure with a zeroed signature, see [`DripAuth::sign`]
+    pub fn new(
+        sam_type: SamType,
+        valid_not_before: DateTime<Utc>,
+        valid_not_after: DateTime<Utc>,
+        evidence: &'a [u8],
+        signer: Hhit,
+    ) -> Result<Self, DripAuthError> {
+        check_evidence(sam_type, evidence)?;
+
+        Ok(Self {
+            sam_type,
+            // only whole seconds are sent
+            valid_not_before: decode_timestamp(&encode_timestamp(valid_not_before)?.to_le_bytes())?,
+            valid_not_after: decode_timestamp(&encode_timestamp(valid_not_after)?.to_le_bytes())?,
+            evidence,
+            signer,
+            signature: [0; SIGNATURE_SIZE],
+        })
+    }
+
+    /// Read the structure of a reassembled Specific Authentication Method payload
+    pub fn parse(payload: &'a AuthPayload) -> Result<Self, DripAuthError> {
+        if payload.auth_type != AuthType::SpecificAuthenticationMethod {
+            return Err(DripAuthError::WrongAuthType(payload.auth_type));
+        }
+
+        Self::from_bytes(payload.data())
+    }
+
+    /// Read the structure of authentication data, starting with the SAM type
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, DripAuthError> {
+        if data.len() < OVERHEAD {
+            return Err(DripAuthError::Truncated(data.len()));
+        }
+
+        let sam_type =
+            SamType::from_known(data[0]).map_err(|_| DripAuthError::UnknownSamType(data[0]))?;
+        let valid_not_before = decode_timestamp(&data[1..5])?;
+        let valid_not_after = decode_timestamp(&data[5..9])?;
+
+        let (signed, signature) = data.split_at(data.len() - SIGNATURE_SIZE);
+        let (evidence, signer) = signed[9..].split_at(signed.len() - 9 - HHIT_SIZE);
+        let signer =
+            Hhit::from_bytes(signer.try_into().unwrap()).map_err(DripAuthError::InvalidSigner)?;
+        check_evidence(sam_type, evidence)?;
+
+        Ok(Self {
+            sam_type,
+            valid_not_before,
+            valid_not_after,
+            evidence,
+            signer,
+            signature: signature.try_into().unwrap(),
+        })
+    }
+
+    /// The payload to send in Authentication messages, see
+    /// [`authentication_pages`](crate::codec::encode::authentication_pages)
+    pub fn to_payload(&self, timestamp: DateTime<Utc>) -> AuthPayload {
+        let mut buf = [0u8; MAX_DATA_SIZE];
+        buf[0] = self.sam_type.into();
+        let len = 1 + self.write_signed(&mut buf[1..]);
+        buf[len..len + SIGNATURE_SIZE].copy_from_slice(&self.signature);
+
+        // the evidence was checked to fit on construction
+        AuthPayload::new(
+            AuthType::SpecificAuthenticationMethod,
+            timestamp,
+            &buf[..len + SIGNATURE_SIZE],
+        )
+        .unwrap()
+    }
+
+    /// Write the signed part of the structure, from the validity period up to the DET of the
+    /// signer
+    fn write_signed(&self, buf: &mut [u8]) -> usize {
+        // timestamps were checked on construction
+        let not_before = encode_timestamp(self.valid_not_before).unwrap();
+        let not_after = encode_timestamp(self.valid_not_after).unwrap();
+        buf[..4].copy_from_slice(&not_before.to_le_bytes());
+        buf[4..8].copy_from_slice(&not_after.to_le_bytes());
+
+        let end = 8 + self.evidence.len();
+        buf[8..end].copy_from_slice(self.evidence);
+        buf[end..end + HHIT_SIZE].copy_from_slice(&self.signer.to_bytes());
+        end + HHIT_SIZE
+    }
+
+    /// Whether `time` is within the validity period
+    pub fn is_valid_at(&self, time: DateTime<Utc>) -> bool {
+        (self.valid_not_before..=self.valid_not_after).contains(&time)
+    }
+
+    pub fn sam_type(&self) -> SamType {
+        self.sam_type
+    }
+
+    pub fn valid_not_before(&self) -> DateTime<Utc> {
+        self.valid_not_before
+    }
+
+    pub fn valid_not_after(&self) -> DateTime<Utc> {
+        self.valid_not_after
+    }
+
+    pub fn evidence(&self) -> &'a [u8] {
+        self.evidence
+    }
+
+    /// The UA endorsed by a Link, `None` for other SAM types
+    pub fn endorsement(&self) -> Option<Endorsement> {
+        if self.sam_type != SamType::Link {
+            return None;
+        }
+
+        let (det, host_identity) = self.evidence.split_at(HHIT_SIZE);
+        Some(Endorsement {
+            // checked on construction
+            det: Hhit::from_bytes(det.try_into().unwrap()).unwrap(),
+            host_identity: host_identity.try_into().unwrap(),
+        })
+    }
+
+    /// The Remote ID messages signed by a Wrapper, `None` for other SAM types
+    pub fn wrapped_messages(
+        &self,
+    ) -> Option<impl Iterator<Item = Result<RemoteIDMessage, DecodeError>> + 'a> {
+        if self.sam_type != SamType::Wrapper {
+            return None;
+        }
+
+        Some(
+            self.evidence
+                .chunks_exact(MESSAGE_SIZE)
+                .map(from_message_buffer),
+        )
+    }
+
+    /// The hashes listed by a Manifest, `None` for other SAM types
+    pub fn manifest(&self) -> Option<Manifest<'a>> {
+        if self.sam_type != SamType::Manifest {
+            return None;
+        }
+
+        let (previous, rest) = self.evidence.split_at(MANIFEST_HASH_SIZE);
+        let (current, message_hashes) = rest.split_at(MANIFEST_HASH_SIZE);
+        Some(Manifest {
+            previous_manifest_hash: previous.try_into().unwrap(),
+            current_manifest_hash: current.try_into().unwrap(),
+            message_hashes,
+        })
+    }
+
+    /// The data link frame signed by a Frame, `None` for other SAM types
+    pub fn frame(&self) -> Option<&'a [u8]> {
+        (self.sam_type == SamType::Frame).then_some(self.evidence)
+    }
+
+    /// Sign the structure with the Ed25519 secret key of the signer
+    #[cfg(feature = "drip")]
+    pub fn sign(&mut self, secret_key: &[u8; 32]) {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let mut buf = [0u8; MAX_DATA_SIZE];
+        let len = self.write_signed(&mut buf);
+        self.signature = SigningKey::from_bytes(secret_key)
+            .sign(&buf[..len])
+            .to_bytes();
+    }
+
+    /// Verify the structure was signed by the owner of `host_identity`, whose DET must be the
+    /// signer. The validity period is not checked, see [`DripAuth::is_valid_at`].
+    #[cfg(feature = "drip")]
+    pub fn verify(&self, host_identity: &[u8; HOST_IDENTITY_SIZE]) -> Result<(), DripAuthError> {
+        use ed25519_dalek::{Signature, VerifyingKey};
+
+        let det = Hhit::from_ed25519(self.signer.raa(), self.signer.hda(), host_identity)
+            .map_err(DripAuthError::InvalidSigner)?;
+        if det != self.signer {
+            return Err(DripAuthError::HostIdentityMismatch);
+        }
+
+        let key = VerifyingKey::from_bytes(host_identity)
+            .map_err(|_| DripAuthError::InvalidHostIdentity)?;
+        let mut buf = [0u8; MAX_DATA_SIZE];
+        let len = self.write_signed(&mut buf);
+        key.verify_strict(&buf[..len], &Signature::from_bytes(&self.signature))
+            .map_err(|_| DripAuthError::InvalidSignature)
+    }
+}
+
+/// The Ed25519 host identity belonging to a secret key
+#[cfg(feature = "drip")]
+pub fn host_identity(secret_key: &[u8; 32]) -> [u8; HOST_IDENTITY_SIZE] {
+    ed25519_dalek::SigningKey::from_bytes(secret_key)
+        .verifying_key()
+        .to_bytes()
+}
+
+/// The evidence of a Link: a registry endorsing the host identity of a UA's DET
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Endorsement {
+    pub det: Hhit,
+    pub host_identity: [u8; HOST_IDENTITY_SIZE],
+}
+
+impl Endorsement {
+    /// The evidence of a Link endorsing this UA
+    pub fn to_bytes(&self) -> [u8; ENDORSEMENT_SIZE] {
+        let mut raw = [0u8; ENDORSEMENT_SIZE];
+        raw[..HHIT_SIZE].copy_from_slice(&self.det.to_bytes());
+        raw[HHIT_SIZE..].copy_from_slice(&self.host_identity);
+        raw
+    }
+}
+
+/// The evidence of a Manifest: the hashes of the previous and current Manifest followed by
+/// the hashes of recently sent messages
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Manifest<'a> {
+    pub previous_manifest_hash: [u8; MANIFEST_HASH_SIZE],
+    pub current_manifest_hash: [u8; MANIFEST_HASH_SIZE],
+    message_hashes: &'a [u8],
+}
+
+impl<'a> Manifest<'a> {
+    pub fn message_hashes(&self) -> impl Iterator<Item = [u8; MANIFEST_HASH_SIZE]> + 'a {
+        self.message_hashes
+            .chunks_exact(MANIFEST_HASH_SIZE)
+            .map(|hash| hash.try_into().unwrap())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SamType {
+    /// A registry's endorsement of the UA's host identity
+    Link,
+    /// Remote ID messages signed by the UA
+    Wrapper,
+    /// Hashes of messages sent by the UA, signed by the UA
+    Manifest,
+    /// A data link frame signed by the UA
+    Frame,
+
+    /// SAM types of other authentication methods
+    Unknown(u8),
+}
+
+impl From<u8> for SamType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => SamType::Link,
+            0x02 => SamType::Wrapper,
+            0x03 => SamType::Manifest,
+            0x04 => SamType::Frame,
+
+            _ => SamType::Unknown(value),
+        }
+    }
+}
+
+from_known!(SamType, Unknown);
+
+impl From<SamType> for u8 {
+    fn from(value: SamType) -> Self {
+        match value {
+            SamType::Link => 0x01,
+            SamType::Wrapper => 0x02,
+            SamType::Manifest => 0x03,
+            SamType::Frame => 0x04,
+            SamType::Unknown(value) => value,
+        }
+    }
+}
+
+fn check_evidence(sam_type: SamType, evidence: &[u8]) -> Result<(), DripAuthError> {
+    if evidence.len() > MAX_EVIDENCE_SIZE {
+        return Err(DripAuthError::EvidenceTooLong(evidence.len()));
+    }
+
+    let len = evidence.len();
+    let valid = match sam_type {
+        SamType::Link => {
+            len == ENDORSEMENT_SIZE
+                && Hhit::from_bytes(evidence[..HHIT_SIZE].try_into().unwrap()).is_ok()
+        }
+        SamType::Wrapper => {
+            len.is_multiple_of(MESSAGE_SIZE)
+                && (1..=MAX_WRAPPED_MESSAGES).contains(&(len / MESSAGE_SIZE))
+        }
+        SamType::Manifest => len > 2 * MANIFEST_HASH_SIZE && len.is_multiple_of(MANIFEST_HASH_SIZE),
+        SamType::Frame => len > 0,
+        SamType::Unknown(value) => return Err(DripAuthError::UnknownSamType(value)),
+    };
+
+    if !valid {
+        return Err(DripAuthError::InvalidEvidence(sam_type));
+    }
+
+    Ok(())
+}
+
+/// Seconds since 00:00:00 01/01/2019, little endian
+fn decode_timestamp(bytes: &[u8]) -> Result<DateTime<Utc>, DripAuthError> {
+    let secs = u32::from_le_bytes(bytes.try_into().unwrap());
+    DateTime::from_timestamp(secs as i64 + TIMESTAMP_EPOCH_2019, 0)
+        .ok_or(DripAuthError::InvalidTimestamp)
+}
+
+fn encode_timestamp(timestamp: DateTime<Utc>) -> Result<u32, DripAuthError> {
+    u32::try_from(timestamp.timestamp() - TIMESTAMP_EPOCH_2019)
+        .map_err(|_| DripAuthError::InvalidTimestamp)
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::codec::auth::AuthReassembler;
+    use crate::codec::encode::{authentication_pages, to_service_data};
+    use crate::data::basic_id::{BasicId, UAType, UasId};
+    use crate::data::drip::hhit::SUITE_EDDSA_CSHAKE128;
+
+    fn timestamp(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_720_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn wrapper() {
+        let det = Hhit::new(10, 20, SUITE_EDDSA_CSHAKE128, 0x03a_d195_2ad0_a69e).unwrap();
+        let basic_id = RemoteIDMessage::BasicID(BasicId::new(UAType::Glider, &UasId::from(det)));
+        let mut evidence = [0u8; 50];
+        evidence[..25].copy_from_slice(&to_service_data(&basic_id));
+        evidence[25..].copy_from_slice(&to_service_data(&basic_id));
+
+        let mut wrapper = DripAuth::new(
+            SamType::Wrapper,
+            timestamp(0),
+            timestamp(300),
+            &evidence,
+            det,
+        )
+        .unwrap();
+        wrapper.signature = [0xA5; 64];
+        let payload = wrapper.to_payload(timestamp(1));
+        assert_eq!(1 + 8 + 50 + 16 + 64, payload.data().len());
+
+        // sent over several pages
+        let mut reassembler = AuthReassembler::<u8, 1>::new();
+        let reassembled = authentication_pages(&payload)
+            .find_map(|page| reassembler.push(1, &page))
+            .unwrap();
+        let parsed = DripAuth::parse(&reassembled).unwrap();
+        assert_eq!(wrapper, parsed);
+        assert!(parsed.is_valid_at(timestamp(300)));
+        assert!(!parsed.is_valid_at(timestamp(301)));
+        assert!(parsed.endorsement().is_none());
+        let messages: std::vec::Vec<_> = parsed.wrapped_messages().unwrap().collect();
+        assert_eq!(std::vec![Ok(basic_id.clone()), Ok(basic_id)], messages);
+
+        assert_eq!(
+            Err(DripAuthError::WrongAuthType(AuthType::UasIdSignature)),
+            DripAuth::parse(&payload_of(AuthType::UasIdSignature, &payload))
+        );
+        let data = payload.data();
+        assert_eq!(
+            Err(DripAuthError::Truncated(88)),
+            DripAuth::from_bytes(&data[..88])
+        );
+        // a byte of the wrapped messages missing
+        let mut data = std::vec::Vec::from(data);
+        data.remove(9);
+        assert_eq!(
+            Err(DripAuthError::InvalidEvidence(SamType::Wrapper)),
+            DripAuth::from_bytes(&data)
+        );
+        data[0] = 0x10;
+        assert_eq!(
+            Err(DripAuthError::UnknownSamType(0x10)),
+            DripAuth::from_bytes(&data)
+        );
+        assert_eq!(
+            Err(DripAuthError::InvalidEvidence(SamType::Manifest)),
+            DripAuth::new(SamType::Manifest, timestamp(0), timestamp(1), &[0; 16], det)
+        );
+        assert_eq!(
+            Err(DripAuthError::InvalidTimestamp),
+            DripAuth::new(
+                SamType::Frame,
+                DateTime::from_timestamp(0, 0).unwrap(),
+                timestamp(1),
+                &[0; 16],
+                det
+            )
+        );
+    }
+
+    fn payload_of(auth_type: AuthType, payload: &AuthPayload) -> AuthPayload {
+        AuthPayload::new(auth_type, payload.timestamp, payload.data()).unwrap()
+    }
+
+    #[cfg(feature = "drip")]
+    #[test]
+    fn verify_link_and_manifest() {
+        let registry_key = [0x11; 32];
+        let registry = Hhit::from_ed25519(10, 20, &host_identity(&registry_key)).unwrap();
+        let ua_key = [0x22; 32];
+        let ua = Endorsement {
+            det: Hhit::from_ed25519(10, 20, &host_identity(&ua_key)).unwrap(),
+            host_identity: host_identity(&ua_key),
+        };
+
+        // the registry endorses the UA
+        let evidence = ua.to_bytes();
+        let mut link = DripAuth::new(
+            SamType::Link,
+            timestamp(0),
+            timestamp(86_400),
+            &evidence,
+            registry,
+        )
+        .unwrap();
+        link.sign(&registry_key);
+        let payload = link.to_payload(timestamp(0));
+        let link = DripAuth::parse(&payload).unwrap();
+        assert_eq!(Ok(()), link.verify(&host_identity(&registry_key)));
+        let endorsed = link.endorsement().unwrap();
+        assert_eq!(ua, endorsed);
+
+        // the UA signs its manifest, checked with the endorsed host identity
+        let evidence: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let mut manifest = DripAuth::new(
+            SamType::Manifest,
+            timestamp(0),
+            timestamp(60),
+            &evidence,
+            ua.det,
+        )
+        .unwrap();
+        manifest.sign(&ua_key);
+        assert_eq!(Ok(()), manifest.verify(&endorsed.host_identity));
+        let hashes = manifest.manifest().unwrap();
+        assert_eq!([8, 9, 10, 11, 12, 13, 14, 15], hashes.current_manifest_hash);
+        assert_eq!(2, hashes.message_hashes().count());
+
+        // spoofed by someone without the UA's key
+        assert_eq!(
+            Err(DripAuthError::HostIdentityMismatch),
+            manifest.verify(&host_identity(&registry_key))
+        );
+        let mut spoofed = manifest.clone();
+        spoofed.sign(&registry_key);
+        assert_eq!(
+            Err(DripAuthError::InvalidSignature),
+            spoofed.verify(&endorsed.host_identity)
+        );
+        let evidence = [0u8; 32];
+        let mut tampered = DripAuth::new(
+            SamType::Manifest,
+            timestamp(0),
+            timestamp(60),
+            &evidence,
+            ua.det,
+        )
+        .unwrap();
+        tampered.signature = manifest.signature;
+        assert_eq!(
+            Err(DripAuthError::InvalidSignature),
+            tampered.verify(&endorsed.host_identity)
+        );
+    }
+}
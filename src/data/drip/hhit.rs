@@ -0,0 +1,335 @@
+//! Hierarchical Host Identity Tags (RFC 9374), the DRIP Entity Tags sent as Specific Session
+//! ID of a Basic ID message.
+//!
+//! An HHIT is an IPv6 address: the 28-bit DRIP prefix, the 32-bit Hierarchy ID made of the
+//! Registered Assigning Authority (RAA) and HHIT Domain Authority (HDA), the 8-bit suite ID
+//! and a 60-bit hash of the host identity.
+
+use core::fmt::{Display, Formatter};
+use core::net::Ipv6Addr;
+
+use crate::data::basic_id::{UasId, SESSION_ID_SIZE};
+
+/// Session ID type of an IETF DRIP HHIT
+pub const SESSION_ID_TYPE: u8 = 1;
+
+/// Size of an HHIT, the rest of the Specific Session ID is zeroed
+pub const HHIT_SIZE: usize = 16;
+
+/// The 28 bits of the DRIP prefix 2001:30::/28
+pub const DRIP_PREFIX: u32 = 0x200_1003;
+
+/// HHIT suite of EdDSA host identities hashed with cSHAKE128
+pub const SUITE_EDDSA_CSHAKE128: u8 = 5;
+
+/// Largest Registered Assigning Authority, 14 bits
+pub const MAX_RAA: u16 = (1 << 14) - 1;
+
+/// Largest HHIT Domain Authority, 18 bits
+pub const MAX_HDA: u32 = (1 << 18) - 1;
+
+/// Largest hash, 60 bits
+pub const MAX_HASH: u64 = (1 << 60) - 1;
+
+/// Context ID of HHITs, the cSHAKE customization string
+#[cfg(feature = "drip")]
+const CONTEXT_ID: [u8; 16] = [
+    0x00, 0xB5, 0xA6, 0x9C, 0x79, 0x5D, 0xF5, 0xD5, 0xF0, 0x08, 0x7F, 0x56, 0x84, 0x3F, 0x2C, 0x40,
+];
+
+/// EdDSA curve of an Ed25519 host identity
+#[cfg(feature = "drip")]
+const CURVE_ED25519: u16 = 1;
+
+const PREFIX_SHIFT: u32 = 100;
+const RAA_SHIFT: u32 = 86;
+const HDA_SHIFT: u32 = 68;
+const SUITE_SHIFT: u32 = 60;
+
+/// A Hierarchical Host Identity Tag, e.g. `2001:30:280:140:503a:d195:2ad0:a69e`.
+///
+/// `Display` writes the IPv6 form read by [`Hhit::parse_str`].
+/// ```
+/// # use remote_id::data::drip::hhit::{Hhit, SUITE_EDDSA_CSHAKE128};
+/// let hhit = Hhit::new(10, 20, SUITE_EDDSA_CSHAKE128, 0x03a_d195_2ad0_a69e).unwrap();
+/// assert_eq!("2001:30:280:140:503a:d195:2ad0:a69e", hhit.to_string());
+/// assert_eq!((10, 20), (hhit.raa(), hhit.hda()));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Hhit(u128);
+
+/// Reasons a value is not an HHIT
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HhitError {
+    /// The tag does not start with the DRIP prefix
+    WrongPrefix,
+    /// The RAA does not fit into 14 bits
+    RaaOutOfRange(u16),
+    /// The HDA does not fit into 18 bits
+    HdaOutOfRange(u32),
+    /// The hash does not fit into 60 bits
+    HashOutOfRange(u64),
+    /// The UAS ID is not a Specific Session ID of type 1
+    NotHhit,
+    /// The bytes after the tag are not zeroed
+    NonZeroPadding,
+    /// Text that is not an IPv6 address
+    InvalidAddress,
+}
+
+impl Display for HhitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HhitError::WrongPrefix => write!(f, "not a DRIP prefix"),
+            HhitError::RaaOutOfRange(raa) => write!(f, "RAA out of range: {raa}"),
+            HhitError::HdaOutOfRange(hda) => write!(f, "HDA out of range: {hda}"),
+            HhitError::HashOutOfRange(hash) => write!(f, "hash out of range: {hash:#x}"),
+            HhitError::NotHhit => write!(f, "not an HHIT session ID"),
+            HhitError::NonZeroPadding => write!(f, "padding after the HHIT is not zeroed"),
+            HhitError::InvalidAddress => write!(f, "invalid IPv6 address"),
+        }
+    }
+}
+
+impl core::error::Error for HhitError {}
+
+impl Hhit {
+    /// Build an HHIT under the DRIP prefix
+    pub fn new(raa: u16, hda: u32, suite_id: u8, hash: u64) -> Result<Self, HhitError> {
+        if raa > MAX_RAA {
+            return Err(HhitError::RaaOutOfRange(raa));
+        }
+        if hda > MAX_HDA {
+            return Err(HhitError::HdaOutOfRange(hda));
+        }
+        if hash > MAX_HASH {
+            return Err(HhitError::HashOutOfRange(hash));
+        }
+
+        Ok(Self(
+            (DRIP_PREFIX as u128) << PREFIX_SHIFT
+                | (raa as u128) << RAA_SHIFT
+                | (hda as u128) << HDA_SHIFT
+                | (suite_id as u128) << SUITE_SHIFT
+                | hash as u128,
+        ))
+    }
+
+    /// Derive the HHIT of an Ed25519 public key registered with the given RAA and HDA.
+    ///
+    /// The hash is the leftmost 60 bits of cSHAKE128 over the prefix, Hierarchy ID and suite
+    /// ID followed by the host identity, customized with the HHIT Context ID. The 68 bits
+    /// ahead of the host identity are padded with zero bits to 9 bytes, the host identity is
+    /// the EdDSA curve followed by the public key.
+    #[cfg(feature = "drip")]
+    pub fn from_ed25519(raa: u16, hda: u32, public_key: &[u8; 32]) -> Result<Self, HhitError> {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+        use sha3::{CShake128, CShake128Core};
+
+        // the hash only fills in the low bits
+        let tag = Self::new(raa, hda, SUITE_EDDSA_CSHAKE128, 0)?;
+
+        let mut hasher = CShake128::from_core(CShake128Core::new(&CONTEXT_ID));
+        hasher.update(&tag.0.to_be_bytes()[..9]);
+        hasher.update(&CURVE_ED25519.to_be_bytes());
+        hasher.update(public_key);
+
+        let mut hash = [0u8; 8];
+        hasher.finalize_xof().read(&mut hash);
+
+        Self::new(
+            raa,
+            hda,
+            SUITE_EDDSA_CSHAKE128,
+            u64::from_be_bytes(hash) >> 4,
+        )
+    }
+
+    /// Read an HHIT in network byte order
+    pub fn from_bytes(raw: &[u8; HHIT_SIZE]) -> Result<Self, HhitError> {
+        let hhit = Self(u128::from_be_bytes(*raw));
+        if hhit.prefix() != DRIP_PREFIX {
+            return Err(HhitError::WrongPrefix);
+        }
+
+        Ok(hhit)
+    }
+
+    /// Read the IPv6 form of an HHIT
+    pub fn parse_str(text: &str) -> Result<Self, HhitError> {
+        let address: Ipv6Addr = text.parse().map_err(|_| HhitError::InvalidAddress)?;
+        Self::from_bytes(&address.octets())
+    }
+
+    /// Read the HHIT of a Specific Session ID
+    pub fn from_session_id(uas_id: &UasId) -> Result<Self, HhitError> {
+        let UasId::SpecificSessionId {
+            session_id_type: SESSION_ID_TYPE,
+            id,
+        } = uas_id
+        else {
+            return Err(HhitError::NotHhit);
+        };
+
+        let (hhit, padding) = id.split_at(HHIT_SIZE);
+        if padding.iter().any(|&b| b != 0) {
+            return Err(HhitError::NonZeroPadding);
+        }
+
+        Self::from_bytes(hhit.try_into().unwrap())
+    }
+
+    /// The Specific Session ID carrying this HHIT
+    pub fn to_session_id(&self) -> UasId {
+        let mut id = [0u8; SESSION_ID_SIZE];
+        id[..HHIT_SIZE].copy_from_slice(&self.to_bytes());
+
+        UasId::SpecificSessionId {
+            session_id_type: SESSION_ID_TYPE,
+            id,
+        }
+    }
+
+    /// The HHIT in network byte order
+    pub fn to_bytes(&self) -> [u8; HHIT_SIZE] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn prefix(&self) -> u32 {
+        (self.0 >> PREFIX_SHIFT) as u32
+    }
+
+    /// Registered Assigning Authority, the upper 14 bits of the Hierarchy ID
+    pub fn raa(&self) -> u16 {
+        (self.0 >> RAA_SHIFT) as u16 & MAX_RAA
+    }
+
+    /// HHIT Domain Authority, the lower 18 bits of the Hierarchy ID
+    pub fn hda(&self) -> u32 {
+        (self.0 >> HDA_SHIFT) as u32 & MAX_HDA
+    }
+
+    pub fn suite_id(&self) -> u8 {
+        (self.0 >> SUITE_SHIFT) as u8
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.0 as u64 & MAX_HASH
+    }
+}
+
+impl From<Hhit> for Ipv6Addr {
+    fn from(hhit: Hhit) -> Self {
+        Ipv6Addr::from(hhit.0)
+    }
+}
+
+impl From<Hhit> for UasId {
+    fn from(hhit: Hhit) -> Self {
+        hhit.to_session_id()
+    }
+}
+
+impl Display for Hhit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Ipv6Addr::from(*self).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::data::basic_id::{BasicId, IdType, UAType};
+
+    // generated by tests/fixtures/generate.py
+    #[cfg(feature = "drip")]
+    const HHIT_ED25519: &[u8; HHIT_SIZE] =
+        include_bytes!("../../../tests/fixtures/hhit_ed25519.bin");
+
+    /// Public key of the first Ed25519 test vector of RFC 8032
+    #[cfg(feature = "drip")]
+    const RFC8032_TEST_1_PUBLIC_KEY: [u8; 32] = [
+        0xD7, 0x5A, 0x98, 0x01, 0x82, 0xB1, 0x0A, 0xB7, 0xD5, 0x4B, 0xFE, 0xD3, 0xC9, 0x64, 0x07,
+        0x3A, 0x0E, 0xE1, 0x72, 0xF3, 0xDA, 0xA6, 0x23, 0x25, 0xAF, 0x02, 0x1A, 0x68, 0xF7, 0x07,
+        0x51, 0x1A,
+    ];
+
+    #[test]
+    fn session_id() {
+        let hhit = Hhit::new(10, 20, SUITE_EDDSA_CSHAKE128, 0x03a_d195_2ad0_a69e).unwrap();
+        let basic_id = BasicId::new(UAType::HelicopterOrMultirotor, &hhit.into());
+        assert_eq!(IdType::SpecificSessionId, basic_id.id_type);
+        assert_eq!([1, 0x20, 0x01, 0x00, 0x30], basic_id.uas_id[..5]);
+
+        let uas_id = basic_id.typed_uas_id().unwrap();
+        assert_eq!(Ok(hhit), Hhit::from_session_id(&uas_id));
+        assert_eq!(
+            Ok(hhit),
+            Hhit::parse_str("2001:30:280:140:503a:d195:2ad0:a69e")
+        );
+        assert_eq!(0x200_1003, hhit.prefix());
+        assert_eq!(SUITE_EDDSA_CSHAKE128, hhit.suite_id());
+        assert_eq!(0x03a_d195_2ad0_a69e, hhit.hash());
+
+        let hhit = Hhit::parse_str("2001:3f:fe3f::").unwrap();
+        assert_eq!((16376, 258048), (hhit.raa(), hhit.hda()));
+
+        assert_eq!(Err(HhitError::WrongPrefix), Hhit::parse_str("2001:db8::1"));
+        assert_eq!(Err(HhitError::InvalidAddress), Hhit::parse_str("2001:30"));
+        assert_eq!(
+            Err(HhitError::RaaOutOfRange(16384)),
+            Hhit::new(16384, 0, SUITE_EDDSA_CSHAKE128, 0)
+        );
+        assert_eq!(
+            Err(HhitError::HdaOutOfRange(1 << 18)),
+            Hhit::new(0, 1 << 18, SUITE_EDDSA_CSHAKE128, 0)
+        );
+        assert_eq!(
+            Err(HhitError::HashOutOfRange(1 << 60)),
+            Hhit::new(0, 0, SUITE_EDDSA_CSHAKE128, 1 << 60)
+        );
+        assert_eq!(
+            Err(HhitError::NotHhit),
+            Hhit::from_session_id(&UasId::SpecificSessionId {
+                session_id_type: 2,
+                id: [0; 19],
+            })
+        );
+        let mut id = [0u8; 19];
+        id[..16].copy_from_slice(&hhit.to_bytes());
+        id[18] = 1;
+        assert_eq!(
+            Err(HhitError::NonZeroPadding),
+            Hhit::from_session_id(&UasId::SpecificSessionId {
+                session_id_type: 1,
+                id,
+            })
+        );
+    }
+
+    #[cfg(feature = "drip")]
+    #[test]
+    fn from_ed25519() {
+        let hhit = Hhit::from_ed25519(10, 20, &RFC8032_TEST_1_PUBLIC_KEY).unwrap();
+        assert_eq!(Ok(hhit), Hhit::from_bytes(HHIT_ED25519));
+        assert_eq!("2001:30:280:140:59fa:423a:404:56b", std::format!("{hhit}"));
+
+        let public_key = [0x42; 32];
+        let hhit = Hhit::from_ed25519(10, 20, &public_key).unwrap();
+        assert_eq!((10, 20), (hhit.raa(), hhit.hda()));
+        assert_eq!(SUITE_EDDSA_CSHAKE128, hhit.suite_id());
+        assert_eq!(Ok(hhit), Hhit::from_ed25519(10, 20, &public_key));
+
+        // the hash covers the key and the hierarchy
+        assert_ne!(
+            hhit.hash(),
+            Hhit::from_ed25519(10, 20, &[0x43; 32]).unwrap().hash()
+        );
+        assert_ne!(
+            hhit.hash(),
+            Hhit::from_ed25519(10, 21, &public_key).unwrap().hash()
+        );
+    }
+}
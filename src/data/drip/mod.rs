@@ -0,0 +1,4 @@
+//! IETF Drone Remote Identification Protocol (DRIP) entities carried in Remote ID messages
+
+pub mod auth;
+pub mod hhit;
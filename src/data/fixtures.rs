@@ -0,0 +1,33 @@
+//! Messages shared by the tests of the transports and capture readers
+
+use super::basic_id::{BasicId, IdType, UAType};
+use super::operator_id::{OperatorId, OperatorIdType};
+use super::self_id::{Description, SelfId, DESCRIPTION_SIZE};
+use super::RemoteIDMessage;
+
+pub(crate) fn basic_id() -> RemoteIDMessage {
+    RemoteIDMessage::BasicID(BasicId {
+        id_type: IdType::SerialNumber,
+        ua_type: UAType::None,
+        uas_id: *b"1596F359746167260749",
+    })
+}
+
+pub(crate) fn operator_id() -> RemoteIDMessage {
+    let mut operator_id = [0u8; 20];
+    operator_id[..16].copy_from_slice(b"FIN87astrdge12k8");
+
+    RemoteIDMessage::OperatorId(OperatorId {
+        id_type: OperatorIdType::OperatorId,
+        operator_id,
+    })
+}
+
+pub(crate) fn self_id() -> RemoteIDMessage {
+    let mut text = [0u8; DESCRIPTION_SIZE];
+    text[..13].copy_from_slice(b"Bridge survey");
+
+    RemoteIDMessage::SelfId(SelfId {
+        description: Description::Text(text),
+    })
+}
@@ -1,4 +1,6 @@
-use core::{time::Duration, u8};
+use core::time::Duration;
+
+use super::coordinate::Coordinate;
 
 pub const MESSAGE_TYPE: u8 = 1;
 
@@ -13,6 +15,9 @@ pub enum VerticalAccuracy {
     LessThan_10_m,
     LessThan_3_m,
     LessThan_1_m,
+
+    /// 7-15: Reserved
+    Reserved(u8),
 }
 
 impl From<u8> for VerticalAccuracy {
@@ -25,21 +30,24 @@ impl From<u8> for VerticalAccuracy {
             4 => Self::LessThan_10_m,
             5 => Self::LessThan_3_m,
             6 => Self::LessThan_1_m,
-            7..=u8::MAX => Self::Unknown,
+            7..=u8::MAX => Self::Reserved(value),
         }
     }
 }
 
-impl Into<u8> for VerticalAccuracy {
-    fn into(self) -> u8 {
-        match self {
-            Self::Unknown => 0,
-            Self::LessThan_150_m => 1,
-            Self::LessThan_45_m => 2,
-            Self::LessThan_25_m => 3,
-            Self::LessThan_10_m => 4,
-            Self::LessThan_3_m => 5,
-            Self::LessThan_1_m => 6,
+from_known!(VerticalAccuracy, Reserved);
+
+impl From<VerticalAccuracy> for u8 {
+    fn from(value: VerticalAccuracy) -> Self {
+        match value {
+            VerticalAccuracy::Unknown => 0,
+            VerticalAccuracy::LessThan_150_m => 1,
+            VerticalAccuracy::LessThan_45_m => 2,
+            VerticalAccuracy::LessThan_25_m => 3,
+            VerticalAccuracy::LessThan_10_m => 4,
+            VerticalAccuracy::LessThan_3_m => 5,
+            VerticalAccuracy::LessThan_1_m => 6,
+            VerticalAccuracy::Reserved(value) => value,
         }
     }
 }
@@ -73,6 +81,9 @@ pub enum HorizontalAccuracy {
     LessThan_3_m,
     ///  <1 m
     LessThan_1_m,
+
+    /// 13-15: Reserved
+    Reserved(u8),
 }
 
 impl From<u8> for HorizontalAccuracy {
@@ -91,27 +102,30 @@ impl From<u8> for HorizontalAccuracy {
             10 => Self::LessThan_10_m,
             11 => Self::LessThan_3_m,
             12 => Self::LessThan_1_m,
-            13..=u8::MAX => Self::Unknown,
+            13..=u8::MAX => Self::Reserved(value),
         }
     }
 }
 
-impl Into<u8> for HorizontalAccuracy {
-    fn into(self) -> u8 {
-        match self {
-            Self::Unknown => 0,
-            Self::LessThan_10_NM => 1,
-            Self::LessThan_4_NM => 2,
-            Self::LessThan_2_NM => 3,
-            Self::LessThan_1_NM => 4,
-            Self::LessThan_half_NM => 5,
-            Self::LessThan_third_NM => 6,
-            Self::LessThan_tenth_NM => 7,
-            Self::LessThan_twentieth_NM => 8,
-            Self::LessThan_30_m => 9,
-            Self::LessThan_10_m => 10,
-            Self::LessThan_3_m => 11,
-            Self::LessThan_1_m => 12,
+from_known!(HorizontalAccuracy, Reserved);
+
+impl From<HorizontalAccuracy> for u8 {
+    fn from(value: HorizontalAccuracy) -> Self {
+        match value {
+            HorizontalAccuracy::Unknown => 0,
+            HorizontalAccuracy::LessThan_10_NM => 1,
+            HorizontalAccuracy::LessThan_4_NM => 2,
+            HorizontalAccuracy::LessThan_2_NM => 3,
+            HorizontalAccuracy::LessThan_1_NM => 4,
+            HorizontalAccuracy::LessThan_half_NM => 5,
+            HorizontalAccuracy::LessThan_third_NM => 6,
+            HorizontalAccuracy::LessThan_tenth_NM => 7,
+            HorizontalAccuracy::LessThan_twentieth_NM => 8,
+            HorizontalAccuracy::LessThan_30_m => 9,
+            HorizontalAccuracy::LessThan_10_m => 10,
+            HorizontalAccuracy::LessThan_3_m => 11,
+            HorizontalAccuracy::LessThan_1_m => 12,
+            HorizontalAccuracy::Reserved(value) => value,
         }
     }
 }
@@ -129,6 +143,9 @@ pub enum SpeedAccuracy {
     LessThan_1_mps,
     /// <0.3 m/s
     LessThan_third_mps,
+
+    /// 5-15: Reserved
+    Reserved(u8),
 }
 
 impl From<u8> for SpeedAccuracy {
@@ -139,19 +156,22 @@ impl From<u8> for SpeedAccuracy {
             2 => Self::LessThan_3_mps,
             3 => Self::LessThan_1_mps,
             4 => Self::LessThan_third_mps,
-            5..=u8::MAX => Self::Unknown,
+            5..=u8::MAX => Self::Reserved(value),
         }
     }
 }
 
-impl Into<u8> for SpeedAccuracy {
-    fn into(self) -> u8 {
-        match self {
-            Self::Unknown => 0,
-            Self::LessThan_10_mps => 1,
-            Self::LessThan_3_mps => 2,
-            Self::LessThan_1_mps => 3,
-            Self::LessThan_third_mps => 4,
+from_known!(SpeedAccuracy, Reserved);
+
+impl From<SpeedAccuracy> for u8 {
+    fn from(value: SpeedAccuracy) -> Self {
+        match value {
+            SpeedAccuracy::Unknown => 0,
+            SpeedAccuracy::LessThan_10_mps => 1,
+            SpeedAccuracy::LessThan_3_mps => 2,
+            SpeedAccuracy::LessThan_1_mps => 3,
+            SpeedAccuracy::LessThan_third_mps => 4,
+            SpeedAccuracy::Reserved(value) => value,
         }
     }
 }
@@ -160,23 +180,38 @@ impl Into<u8> for SpeedAccuracy {
 pub struct Location {
     pub operational_status: OperationalStatus,
     pub height_type: HeightType,
-    // pub ew_direction_segment: EastWestDirectionSegment,
-    /// Speed Multiplier enables speeds up to 254.25 m/s. Only use 1 when speed exceeds 63.75 m/s and add 63.75.
-    // pub speed_multiplier: f32,
-    pub speed: f32,
-    pub vertical_speed: f32,
-    pub pressure_altitude: f32,
-    pub geodetic_altitude: f32,
+    /// Ground speed in m/s, up to 254.25 m/s.
+    ///
+    /// Sent in 0.25 m/s steps up to 63.75 m/s, above that the speed multiplier is set and
+    /// the steps are 0.75 m/s. `None` when unknown (sent as 255 m/s).
+    pub speed: Option<f32>,
+    /// Vertical speed in m/s, positive up, within ±62 m/s in 0.5 m/s steps.
+    ///
+    /// `None` when unknown (sent as 63 m/s).
+    pub vertical_speed: Option<f32>,
+    /// `None` when unknown (sent as -1000 m).
+    pub pressure_altitude: Option<f32>,
+    /// `None` when unknown (sent as -1000 m).
+    pub geodetic_altitude: Option<f32>,
     /// Direction expressed as the route course measured clockwise from true north.
-    pub track_direction: u16,
+    ///
+    /// Sent as 0..179 with the East/West direction segment set for 180 and above.
+    /// `None` when unknown (sent as 361).
+    pub track_direction: Option<u16>,
     pub horizontal_accuracy: HorizontalAccuracy,
     pub vertical_accuracy: VerticalAccuracy,
-    pub latidute: f32,
-    pub longitude: f32,
-    pub height: f32,
+    /// `None` when unknown, latitude and longitude are then both sent as 0.
+    pub latidute: Option<Coordinate>,
+    /// `None` when unknown, latitude and longitude are then both sent as 0.
+    pub longitude: Option<Coordinate>,
+    /// `None` when unknown (sent as -1000 m).
+    pub height: Option<f32>,
     pub baro_altitude_accuracy: VerticalAccuracy,
     pub speed_accuracy: SpeedAccuracy,
-    pub timestamp: f32,
+    /// Seconds since the last full hour relative to UTC time, in 0.1 s steps.
+    ///
+    /// `None` when unknown (sent as 0xFFFF).
+    pub timestamp: Option<f32>,
     pub timestamp_accuracy: Option<Duration>,
 }
 
@@ -187,30 +222,37 @@ pub enum OperationalStatus {
     Airborne,
     Emergency,
     RemoteIdSystemFailure,
+
+    /// 5-15: Reserved
+    Unknown(u8),
 }
 
 impl From<u8> for OperationalStatus {
     fn from(value: u8) -> Self {
         match value {
+            0 => OperationalStatus::Undeclared,
             1 => OperationalStatus::Ground,
             2 => OperationalStatus::Airborne,
             3 => OperationalStatus::Emergency,
             4 => OperationalStatus::RemoteIdSystemFailure,
 
-            _ => OperationalStatus::Undeclared,
+            _ => OperationalStatus::Unknown(value),
         }
     }
 }
 
-impl Into<u8> for OperationalStatus {
-    fn into(self) -> u8 {
-        match self {
+from_known!(OperationalStatus, Unknown);
+
+impl From<OperationalStatus> for u8 {
+    fn from(value: OperationalStatus) -> Self {
+        match value {
             OperationalStatus::Ground => 1,
             OperationalStatus::Airborne => 2,
             OperationalStatus::Emergency => 3,
             OperationalStatus::RemoteIdSystemFailure => 4,
 
             OperationalStatus::Undeclared => 0,
+            OperationalStatus::Unknown(value) => value,
         }
     }
 }
@@ -232,37 +274,11 @@ impl From<u8> for HeightType {
     }
 }
 
-impl Into<u8> for HeightType {
-    fn into(self) -> u8 {
-        match self {
+impl From<HeightType> for u8 {
+    fn from(value: HeightType) -> Self {
+        match value {
             HeightType::AboveTakeoff => 0,
             HeightType::AboveGroundLevel => 1,
         }
     }
 }
-
-// #[derive(Debug, Copy, Clone, PartialEq)]
-// pub enum EastWestDirectionSegment {
-//     LowerThan180 = 0,
-//     GreaterOrEqual180 = 1,
-// }
-
-// impl From<u8> for EastWestDirectionSegment {
-//     fn from(value: u8) -> Self {
-//         match value {
-//             0 => EastWestDirectionSegment::LowerThan180,
-//             1 => EastWestDirectionSegment::GreaterOrEqual180,
-
-//             _ => EastWestDirectionSegment::LowerThan180,
-//         }
-//     }
-// }
-
-// impl Into<u8> for EastWestDirectionSegment {
-//     fn into(self) -> u8 {
-//         match self {
-//             EastWestDirectionSegment::LowerThan180 => 0,
-//             EastWestDirectionSegment::GreaterOrEqual180 => 1,
-//         }
-//     }
-// }
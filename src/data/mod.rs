@@ -1,8 +1,30 @@
+/// Strict counterpart to the lenient `From<u8>` of an enum that keeps unassigned values in
+/// `$variant`. `TryFrom<u8>` cannot be used, it is already implemented through `From<u8>`.
+macro_rules! from_known {
+    ($ty:ty, $variant:ident) => {
+        impl $ty {
+            /// Convert a value assigned by the specification, reserved and private use values
+            /// are rejected
+            pub fn from_known(value: u8) -> Result<Self, $crate::data::UnknownValue> {
+                match Self::from(value) {
+                    Self::$variant(value) => Err($crate::data::UnknownValue(value)),
+                    known => Ok(known),
+                }
+            }
+        }
+    };
+}
+
+pub mod authentication;
 pub mod basic_id;
+pub mod coordinate;
+pub mod drip;
+#[cfg(test)]
+pub(crate) mod fixtures;
 pub mod location;
 pub mod operator_id;
-pub mod system;
 pub mod self_id;
+pub mod system;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum RemoteIDMessage {
@@ -12,16 +34,66 @@ pub enum RemoteIDMessage {
     /// Provides location, altitude, direction, and speed of UA
     Location(location::Location),
 
-    // /// Provides authentication data for the UA
-    // Authentication,
+    /// Provides authentication data for the UA
+    Authentication(authentication::Authentication),
 
-    // /// Message that can be used by Operators to identify themselves and the purpose of an operation
-    // SelfId,
     /// Includes Remote Pilot location and multiple aircraft information (group) if applicable, and additional system information
     System(system::System),
 
     /// Provides Operator ID
     OperatorId(operator_id::OperatorId),
 
-    SelfId(self_id::SelfId)
+    /// Message that can be used by Operators to identify themselves and the purpose of an operation
+    SelfId(self_id::SelfId),
+}
+
+/// Revision of ASTM F3411 a message follows, sent in the low nibble of its header
+#[allow(non_camel_case_types)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProtocolVersion {
+    /// F3411-19, System messages end after the area floor and Location messages carry no
+    /// timestamp accuracy
+    F3411_19,
+    /// F3411-20
+    F3411_20,
+    /// F3411-22a
+    #[default]
+    F3411_22a,
+}
+
+impl TryFrom<u8> for ProtocolVersion {
+    type Error = UnknownValue;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        // 3-14: Reserved, 15: Available for private use
+        match value {
+            0 => Ok(ProtocolVersion::F3411_19),
+            1 => Ok(ProtocolVersion::F3411_20),
+            2 => Ok(ProtocolVersion::F3411_22a),
+
+            _ => Err(UnknownValue(value)),
+        }
+    }
+}
+
+impl From<ProtocolVersion> for u8 {
+    fn from(value: ProtocolVersion) -> Self {
+        match value {
+            ProtocolVersion::F3411_19 => 0,
+            ProtocolVersion::F3411_20 => 1,
+            ProtocolVersion::F3411_22a => 2,
+        }
+    }
 }
+
+/// A value that is reserved, for private use or otherwise not assigned by the specification
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnknownValue(pub u8);
+
+impl core::fmt::Display for UnknownValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unknown value: {}", self.0)
+    }
+}
+
+impl core::error::Error for UnknownValue {}
@@ -1,12 +1,251 @@
+use core::fmt::{Display, Formatter};
+
+use crate::MAX_ID_BYTE_SIZE;
 
 pub const MESSAGE_TYPE: u8 = 5;
 
+/// Size of the ISO 3166 alpha-3 country code at the start of an EU operator ID
+pub const COUNTRY_CODE_SIZE: usize = 3;
+
+/// Size of the body of an EU operator ID, between the country code and the checksum
+pub const EU_BODY_SIZE: usize = 12;
+
+/// Size of the secret that completes an EU operator ID, it is never broadcast
+pub const EU_SECRET_SIZE: usize = 3;
+
+/// Size of the broadcast form of an EU operator ID: country code, body and checksum
+pub const EU_BROADCAST_SIZE: usize = COUNTRY_CODE_SIZE + EU_BODY_SIZE + 1;
+
+/// Size of a full EU operator ID: the broadcast form, a dash and the secret
+pub const EU_FULL_SIZE: usize = EU_BROADCAST_SIZE + 1 + EU_SECRET_SIZE;
+
+/// Digits and lower case letters, in the order of their Luhn mod 36 code points
+const EU_ALPHABET: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct OperatorId {
     pub id_type: OperatorIdType,
     pub operator_id: [u8; 20],
 }
 
+impl OperatorId {
+    /// The operator ID read as the broadcast form of an EU operator registration number
+    pub fn eu_operator_id(&self) -> Result<EuOperatorId, EuOperatorIdError> {
+        EuOperatorId::from_bytes(&self.operator_id)
+    }
+}
+
+impl From<&EuOperatorId> for OperatorId {
+    fn from(id: &EuOperatorId) -> Self {
+        Self {
+            id_type: OperatorIdType::OperatorId,
+            operator_id: id.to_bytes(),
+        }
+    }
+}
+
+/// Broadcast form of an EN 4709-002 operator registration number, e.g. "FIN87astrdge12k8".
+///
+/// The full number issued to an operator, e.g. "FIN87astrdge12k8-xyz", adds a dash and a
+/// 3-character secret. The checksum is computed over the body and the secret, so it can only
+/// be verified with the full number. Receivers can merely check the broadcast form is
+/// well-formed.
+/// ```
+/// # use remote_id::data::operator_id::EuOperatorId;
+/// let id = EuOperatorId::new("FIN", "87astrdge12k", "xyz").unwrap();
+/// assert_eq!("FIN87astrdge12k8", id.as_str());
+/// assert_eq!(id, EuOperatorId::verify("FIN87astrdge12k8-xyz").unwrap());
+/// assert!(id.check_secret("xyw").is_err());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EuOperatorId([u8; EU_BROADCAST_SIZE]);
+
+/// Reasons a text is not an EU operator ID
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EuOperatorIdError {
+    /// The ID is not as long as its form requires
+    InvalidLength(usize),
+    /// The country code is not three upper case letters
+    InvalidCountryCode,
+    /// A character other than a digit or lower case letter, or a missing dash before the
+    /// secret. The position is counted from the start of the ID.
+    InvalidCharacter { position: usize },
+    /// The checksum does not match the body and secret
+    ChecksumMismatch,
+    /// The bytes after the ID are not zeroed
+    NonZeroPadding,
+}
+
+impl Display for EuOperatorIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EuOperatorIdError::InvalidLength(len) => write!(f, "invalid length: {len}"),
+            EuOperatorIdError::InvalidCountryCode => write!(f, "invalid country code"),
+            EuOperatorIdError::InvalidCharacter { position } => {
+                write!(f, "invalid character at position {position}")
+            }
+            EuOperatorIdError::ChecksumMismatch => write!(f, "checksum mismatch"),
+            EuOperatorIdError::NonZeroPadding => write!(f, "padding after the ID is not zeroed"),
+        }
+    }
+}
+
+impl core::error::Error for EuOperatorIdError {}
+
+impl EuOperatorId {
+    /// Build the operator ID of a country code, body and secret, adding the checksum. The
+    /// full number is the broadcast form followed by a dash and the secret.
+    pub fn new(country_code: &str, body: &str, secret: &str) -> Result<Self, EuOperatorIdError> {
+        if country_code.len() != COUNTRY_CODE_SIZE {
+            return Err(EuOperatorIdError::InvalidCountryCode);
+        }
+        if body.len() != EU_BODY_SIZE {
+            return Err(EuOperatorIdError::InvalidLength(
+                COUNTRY_CODE_SIZE + body.len() + 1,
+            ));
+        }
+
+        let mut raw = [0u8; EU_BROADCAST_SIZE];
+        raw[..COUNTRY_CODE_SIZE].copy_from_slice(country_code.as_bytes());
+        raw[COUNTRY_CODE_SIZE..EU_BROADCAST_SIZE - 1].copy_from_slice(body.as_bytes());
+        // any valid character, checked and replaced once the body is known to be valid
+        raw[EU_BROADCAST_SIZE - 1] = b'0';
+
+        let mut id = Self::from_broadcast(raw)?;
+        id.0[EU_BROADCAST_SIZE - 1] = id.expected_checksum(secret)?;
+        Ok(id)
+    }
+
+    /// Verify a full operator ID including the secret, e.g. "FIN87astrdge12k8-xyz", and
+    /// derive its broadcast form
+    pub fn verify(full: &str) -> Result<Self, EuOperatorIdError> {
+        if full.len() != EU_FULL_SIZE {
+            return Err(EuOperatorIdError::InvalidLength(full.len()));
+        }
+
+        let (broadcast, secret) = full.as_bytes().split_at(EU_BROADCAST_SIZE);
+        let id = Self::from_broadcast(broadcast.try_into().unwrap())?;
+        if secret[0] != b'-' {
+            return Err(EuOperatorIdError::InvalidCharacter {
+                position: EU_BROADCAST_SIZE,
+            });
+        }
+
+        // the split is within ASCII, validated above
+        id.check_secret(&full[EU_BROADCAST_SIZE + 1..])?;
+        Ok(id)
+    }
+
+    /// Check the broadcast form of an operator ID is well-formed, e.g. "FIN87astrdge12k8"
+    pub fn parse(broadcast: &str) -> Result<Self, EuOperatorIdError> {
+        let raw = broadcast
+            .as_bytes()
+            .try_into()
+            .map_err(|_| EuOperatorIdError::InvalidLength(broadcast.len()))?;
+        Self::from_broadcast(raw)
+    }
+
+    /// Check the Operator ID field of a received message holds a well-formed broadcast form,
+    /// padded with NULs
+    pub fn from_bytes(raw: &[u8; MAX_ID_BYTE_SIZE]) -> Result<Self, EuOperatorIdError> {
+        let len = raw.iter().position(|&b| b == 0).unwrap_or(MAX_ID_BYTE_SIZE);
+        if len != EU_BROADCAST_SIZE {
+            return Err(EuOperatorIdError::InvalidLength(len));
+        }
+        if raw[len..].iter().any(|&b| b != 0) {
+            return Err(EuOperatorIdError::NonZeroPadding);
+        }
+
+        Self::from_broadcast(raw[..len].try_into().unwrap())
+    }
+
+    fn from_broadcast(raw: [u8; EU_BROADCAST_SIZE]) -> Result<Self, EuOperatorIdError> {
+        let (country_code, rest) = raw.split_at(COUNTRY_CODE_SIZE);
+        if !country_code.iter().all(u8::is_ascii_uppercase) {
+            return Err(EuOperatorIdError::InvalidCountryCode);
+        }
+        if let Some(i) = rest.iter().position(|&b| luhn_code_point(b).is_none()) {
+            return Err(EuOperatorIdError::InvalidCharacter {
+                position: COUNTRY_CODE_SIZE + i,
+            });
+        }
+
+        Ok(Self(raw))
+    }
+
+    /// Check the secret completes this operator ID
+    pub fn check_secret(&self, secret: &str) -> Result<(), EuOperatorIdError> {
+        if self.expected_checksum(secret)? != self.0[EU_BROADCAST_SIZE - 1] {
+            return Err(EuOperatorIdError::ChecksumMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Luhn mod 36 check character of the body followed by the secret
+    fn expected_checksum(&self, secret: &str) -> Result<u8, EuOperatorIdError> {
+        if secret.len() != EU_SECRET_SIZE {
+            return Err(EuOperatorIdError::InvalidLength(
+                EU_BROADCAST_SIZE + 1 + secret.len(),
+            ));
+        }
+
+        let body = &self.0[COUNTRY_CODE_SIZE..EU_BROADCAST_SIZE - 1];
+        let mut sum = 0;
+        // doubled from the rightmost character on
+        for (i, &b) in body.iter().chain(secret.as_bytes()).rev().enumerate() {
+            let code_point = luhn_code_point(b).ok_or(EuOperatorIdError::InvalidCharacter {
+                position: EU_FULL_SIZE - 1 - i,
+            })?;
+            let addend = if i % 2 == 0 {
+                2 * code_point
+            } else {
+                code_point
+            };
+            sum += addend / 36 + addend % 36;
+        }
+
+        Ok(EU_ALPHABET[(36 - sum % 36) % 36])
+    }
+
+    /// The ISO 3166 alpha-3 code of the registering country
+    pub fn country_code(&self) -> &str {
+        &self.as_str()[..COUNTRY_CODE_SIZE]
+    }
+
+    /// The body assigned by the registering country
+    pub fn body(&self) -> &str {
+        &self.as_str()[COUNTRY_CODE_SIZE..EU_BROADCAST_SIZE - 1]
+    }
+
+    pub fn checksum(&self) -> char {
+        self.0[EU_BROADCAST_SIZE - 1] as char
+    }
+
+    pub fn as_str(&self) -> &str {
+        // validated to be ASCII on construction
+        core::str::from_utf8(&self.0).unwrap()
+    }
+
+    /// The Operator ID field of a message, the broadcast form padded with NULs
+    pub fn to_bytes(&self) -> [u8; MAX_ID_BYTE_SIZE] {
+        let mut raw = [0u8; MAX_ID_BYTE_SIZE];
+        raw[..EU_BROADCAST_SIZE].copy_from_slice(&self.0);
+        raw
+    }
+}
+
+impl Display for EuOperatorId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Position of a digit or lower case letter in [`EU_ALPHABET`]
+fn luhn_code_point(b: u8) -> Option<usize> {
+    EU_ALPHABET.iter().position(|&c| c == b)
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum OperatorIdType {
     OperatorId,
@@ -26,11 +265,81 @@ impl From<u8> for OperatorIdType {
     }
 }
 
-impl Into<u8> for OperatorIdType {
-    fn into(self) -> u8 {
-        match self {
+from_known!(OperatorIdType, Unknown);
+
+impl From<OperatorIdType> for u8 {
+    fn from(value: OperatorIdType) -> Self {
+        match value {
             OperatorIdType::OperatorId => 0,
             OperatorIdType::Unknown(value) => value,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+
+    #[test]
+    fn eu_operator_id() {
+        let operator_id = OperatorId {
+            id_type: OperatorIdType::OperatorId,
+            operator_id: *b"FIN87astrdge12k8\0\0\0\0",
+        };
+        let id = operator_id.eu_operator_id().unwrap();
+        assert_eq!("FIN", id.country_code());
+        assert_eq!("87astrdge12k", id.body());
+        assert_eq!('8', id.checksum());
+        assert_eq!(Ok(()), id.check_secret("xyz"));
+        assert_eq!(Ok(id), EuOperatorId::verify("FIN87astrdge12k8-xyz"));
+        assert_eq!(Ok(id), EuOperatorId::parse("FIN87astrdge12k8"));
+        assert_eq!(Ok(id), EuOperatorId::new("FIN", "87astrdge12k", "xyz"));
+        assert_eq!(operator_id, OperatorId::from(&id));
+
+        // every character takes part in the checksum
+        let id = EuOperatorId::new("SWE", "0123456789ab", "000").unwrap();
+        assert_eq!(Ok(id), EuOperatorId::verify(&std::format!("{id}-000")));
+        assert_eq!(
+            Err(EuOperatorIdError::ChecksumMismatch),
+            EuOperatorId::verify("FIN87astrdge12k8-xzy")
+        );
+        assert_eq!(
+            Err(EuOperatorIdError::ChecksumMismatch),
+            EuOperatorId::verify("FIN87astrdeg12k8-xyz")
+        );
+
+        assert_eq!(
+            Err(EuOperatorIdError::InvalidLength(16)),
+            EuOperatorId::verify("FIN87astrdge12k8")
+        );
+        assert_eq!(
+            Err(EuOperatorIdError::InvalidCharacter { position: 16 }),
+            EuOperatorId::verify("FIN87astrdge12k8_xyz")
+        );
+        assert_eq!(
+            Err(EuOperatorIdError::InvalidCharacter { position: 19 }),
+            EuOperatorId::verify("FIN87astrdge12k8-xyZ")
+        );
+        assert_eq!(
+            Err(EuOperatorIdError::InvalidCharacter { position: 5 }),
+            EuOperatorId::parse("FIN87Astrdge12k8")
+        );
+        assert_eq!(
+            Err(EuOperatorIdError::InvalidCountryCode),
+            EuOperatorId::parse("Fin87astrdge12k8")
+        );
+        assert_eq!(
+            Err(EuOperatorIdError::InvalidLength(15)),
+            EuOperatorId::parse("FIN87astrdge12k")
+        );
+
+        let mut raw = id.to_bytes();
+        raw[19] = b'x';
+        assert_eq!(
+            Err(EuOperatorIdError::NonZeroPadding),
+            EuOperatorId::from_bytes(&raw)
+        );
+    }
+}
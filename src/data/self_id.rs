@@ -1,11 +1,74 @@
 pub const MESSAGE_TYPE: u8 = 0x3;
 
+pub const DESCRIPTION_SIZE: usize = 23;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SelfId {
     pub description: Description,
 }
 
+impl SelfId {
+    /// The description as ASCII text with trailing NULs removed, `None` if it is not ASCII.
+    pub fn text(&self) -> Option<&str> {
+        self.description.as_str()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Description {
-    Text([u8; 23]),
+    /// Text description of the operation
+    Text([u8; DESCRIPTION_SIZE]),
+    /// Emergency description
+    Emergency([u8; DESCRIPTION_SIZE]),
+    /// Extended status description
+    ExtendedStatus([u8; DESCRIPTION_SIZE]),
+
+    /// 3-200: Reserved
+    Reserved(u8, [u8; DESCRIPTION_SIZE]),
+    /// 201-255: Available for private use
+    PrivateUse(u8, [u8; DESCRIPTION_SIZE]),
+}
+
+impl Description {
+    pub fn new(description_type: u8, text: [u8; DESCRIPTION_SIZE]) -> Self {
+        match description_type {
+            0 => Description::Text(text),
+            1 => Description::Emergency(text),
+            2 => Description::ExtendedStatus(text),
+            3..=200 => Description::Reserved(description_type, text),
+            201.. => Description::PrivateUse(description_type, text),
+        }
+    }
+
+    pub fn description_type(&self) -> u8 {
+        match self {
+            Description::Text(_) => 0,
+            Description::Emergency(_) => 1,
+            Description::ExtendedStatus(_) => 2,
+            Description::Reserved(value, _) | Description::PrivateUse(value, _) => *value,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; DESCRIPTION_SIZE] {
+        match self {
+            Description::Text(text)
+            | Description::Emergency(text)
+            | Description::ExtendedStatus(text)
+            | Description::Reserved(_, text)
+            | Description::PrivateUse(_, text) => text,
+        }
+    }
+
+    /// The description as ASCII text with trailing NULs removed, `None` if it is not ASCII.
+    pub fn as_str(&self) -> Option<&str> {
+        let bytes = self.as_bytes();
+        let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        let text = &bytes[..end];
+
+        if !text.is_ascii() {
+            return None;
+        }
+
+        core::str::from_utf8(text).ok()
+    }
 }
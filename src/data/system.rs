@@ -1,26 +1,38 @@
 use chrono::{DateTime, Utc};
 
+use super::coordinate::Coordinate;
+
 pub const MESSAGE_TYPE: u8 = 4;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct System {
+    /// Always `Undeclared` in F3411-19 messages, as is the UA classification
     pub classification_type: ClassificationType,
     pub operator_location_type: OperatorLocationType,
-    pub operator_latidute: f32,
-    pub operator_longitude: f32,
+    pub operator_latidute: Coordinate,
+    pub operator_longitude: Coordinate,
     pub area_count: u16,
     pub area_radius: f32,
     pub area_ceiling: f32,
     pub area_floor: f32,
     pub ua_classification: UaClassification,
+    /// -1000 m when unknown, always the case for F3411-19 messages
     pub operator_altitude: f32,
-    pub timestamp: DateTime<Utc>,
+    /// `None` for F3411-19 messages, which do not carry a timestamp.
+    ///
+    /// Later versions have no "unknown" value, `None` is rejected by
+    /// [`EncodeMode::Strict`](crate::codec::encode::EncodeMode::Strict) and sent as 00:00:00
+    /// 01/01/2019 by [`EncodeMode::Clamp`](crate::codec::encode::EncodeMode::Clamp).
+    pub timestamp: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ClassificationType {
-    Undeclared = 0,
-    EuropeanUnion = 1,
+    Undeclared,
+    EuropeanUnion,
+
+    /// 2-7: Reserved
+    Unknown(u8),
 }
 
 impl From<u8> for ClassificationType {
@@ -29,17 +41,31 @@ impl From<u8> for ClassificationType {
             0 => ClassificationType::Undeclared,
             1 => ClassificationType::EuropeanUnion,
 
-            // reserved classifications
-            2.. => ClassificationType::Undeclared,
+            _ => ClassificationType::Unknown(value),
+        }
+    }
+}
+
+from_known!(ClassificationType, Unknown);
+
+impl From<ClassificationType> for u8 {
+    fn from(value: ClassificationType) -> Self {
+        match value {
+            ClassificationType::Undeclared => 0,
+            ClassificationType::EuropeanUnion => 1,
+            ClassificationType::Unknown(value) => value,
         }
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum OperatorLocationType {
-    TakeOff = 0,
-    Dynamic = 1,
-    Fixed = 2,
+    TakeOff,
+    Dynamic,
+    Fixed,
+
+    /// 3: Reserved
+    Unknown(u8),
 }
 
 impl From<u8> for OperatorLocationType {
@@ -49,7 +75,20 @@ impl From<u8> for OperatorLocationType {
             1 => OperatorLocationType::Dynamic,
             2 => OperatorLocationType::Fixed,
 
-            3.. => OperatorLocationType::TakeOff,
+            _ => OperatorLocationType::Unknown(value),
+        }
+    }
+}
+
+from_known!(OperatorLocationType, Unknown);
+
+impl From<OperatorLocationType> for u8 {
+    fn from(value: OperatorLocationType) -> Self {
+        match value {
+            OperatorLocationType::TakeOff => 0,
+            OperatorLocationType::Dynamic => 1,
+            OperatorLocationType::Fixed => 2,
+            OperatorLocationType::Unknown(value) => value,
         }
     }
 }
@@ -75,6 +114,9 @@ pub enum UaCategory {
     Open,
     Specific,
     Certified,
+
+    /// 4-15: Reserved
+    Unknown(u8),
 }
 
 impl From<u8> for UaCategory {
@@ -85,18 +127,21 @@ impl From<u8> for UaCategory {
             2 => UaCategory::Specific,
             3 => UaCategory::Certified,
 
-            _ => UaCategory::Undefined,
+            _ => UaCategory::Unknown(value),
         }
     }
 }
 
-impl Into<u8> for UaCategory {
-    fn into(self) -> u8 {
-        match self {
+from_known!(UaCategory, Unknown);
+
+impl From<UaCategory> for u8 {
+    fn from(value: UaCategory) -> Self {
+        match value {
             UaCategory::Undefined => 0,
             UaCategory::Open => 1,
             UaCategory::Specific => 2,
             UaCategory::Certified => 3,
+            UaCategory::Unknown(value) => value,
         }
     }
 }
@@ -111,6 +156,9 @@ pub enum UaClass {
     Class4,
     Class5,
     Class6,
+
+    /// 8-15: Reserved
+    Unknown(u8),
 }
 
 impl From<u8> for UaClass {
@@ -125,14 +173,16 @@ impl From<u8> for UaClass {
             6 => UaClass::Class5,
             7 => UaClass::Class6,
 
-            _ => UaClass::Undefined,
+            _ => UaClass::Unknown(value),
         }
     }
 }
 
-impl Into<u8> for UaClass {
-    fn into(self) -> u8 {
-        match self {
+from_known!(UaClass, Unknown);
+
+impl From<UaClass> for u8 {
+    fn from(value: UaClass) -> Self {
+        match value {
             UaClass::Undefined => 0,
             UaClass::Class0 => 1,
             UaClass::Class1 => 2,
@@ -141,6 +191,7 @@ impl Into<u8> for UaClass {
             UaClass::Class4 => 5,
             UaClass::Class5 => 6,
             UaClass::Class6 => 7,
+            UaClass::Unknown(value) => value,
         }
     }
 }
@@ -1,7 +1,9 @@
 #![no_std]
 
+pub mod capture;
 pub mod codec;
 pub mod data;
+pub mod transport;
 
 const MAX_ID_BYTE_SIZE: usize = 20;
 
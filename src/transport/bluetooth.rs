@@ -0,0 +1,425 @@
+use crate::codec::decode::{decode_message_pack, from_message_buffer, DecodeError, MessagePack};
+use crate::codec::encode::{encode_message, encode_message_pack, EncodeError, EncodeMode};
+use crate::codec::{MessageKind, MAX_MESSAGE_PACK_SIZE};
+use crate::data::RemoteIDMessage;
+use crate::{get_bits, OPEN_DRONE_ID_AD_CODE, REMOTE_ID_SERVICE_UUID};
+
+use super::Payload;
+
+/// AD type of the Flags structure
+pub const FLAGS_AD_TYPE: u8 = 0x01;
+
+/// AD type of Service Data with a 16-bit UUID
+pub const SERVICE_DATA_AD_TYPE: u8 = 0x16;
+
+/// 16-bit form of [`REMOTE_ID_SERVICE_UUID`]
+pub const REMOTE_ID_SERVICE_UUID_16: u16 = (REMOTE_ID_SERVICE_UUID >> 96) as u16;
+
+/// LE General Discoverable Mode, BR/EDR Not Supported
+pub const DEFAULT_FLAGS: u8 = 0x06;
+
+/// Size of the advertising data of a legacy (Bluetooth 4) advertisement
+pub const LEGACY_ADVERTISING_DATA_SIZE: usize = 31;
+
+/// UUID, app code and message counter in front of the message
+const SERVICE_DATA_HEADER_SIZE: usize = 2 + 1 + 1;
+
+/// Size of the advertising data of an extended (Bluetooth 5) advertisement carrying a full
+/// Message Pack, including the Flags AD
+pub const MAX_EXTENDED_ADVERTISING_DATA_SIZE: usize =
+    3 + 2 + SERVICE_DATA_HEADER_SIZE + MAX_MESSAGE_PACK_SIZE;
+
+/// Message counter of a broadcast, incremented for every new message
+///
+/// Legacy advertisements keep one counter per message type, extended advertisements a single
+/// one for the Message Pack.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct MessageCounter(u8);
+
+impl MessageCounter {
+    pub const fn new(start: u8) -> Self {
+        Self(start)
+    }
+
+    /// The counter value for the next message, wrapping around after 255
+    pub fn advance(&mut self) -> u8 {
+        let counter = self.0;
+        self.0 = self.0.wrapping_add(1);
+        counter
+    }
+}
+
+/// Write the AD structures carrying `message`, returning the number of bytes written.
+///
+/// `message` is a single encoded message or a Message Pack, it is preceded by a Flags AD
+/// if `flags` are given.
+pub fn encode_advertising_data(
+    buf: &mut [u8],
+    flags: Option<u8>,
+    counter: u8,
+    message: &[u8],
+) -> Result<usize, EncodeError> {
+    // the AD length byte covers the type, UUID, app code, counter and message
+    let ad_length = 1 + SERVICE_DATA_HEADER_SIZE + message.len();
+    if ad_length > u8::MAX as usize {
+        return Err(EncodeError::FieldOutOfRange {
+            field: "service data length",
+            value: ad_length as f64,
+        });
+    }
+
+    let flags_size = if flags.is_some() { 3 } else { 0 };
+    let expected = flags_size + 1 + ad_length;
+    if buf.len() < expected {
+        return Err(EncodeError::BufferTooSmall {
+            expected,
+            actual: buf.len(),
+        });
+    }
+
+    let mut offset = 0;
+    if let Some(flags) = flags {
+        buf[..3].copy_from_slice(&[2, FLAGS_AD_TYPE, flags]);
+        offset += 3;
+    }
+
+    buf[offset] = ad_length as u8;
+    buf[offset + 1] = SERVICE_DATA_AD_TYPE;
+    buf[(offset + 2)..(offset + 4)].copy_from_slice(&REMOTE_ID_SERVICE_UUID_16.to_le_bytes());
+    buf[offset + 4] = OPEN_DRONE_ID_AD_CODE;
+    buf[offset + 5] = counter;
+    offset += 6;
+
+    buf[offset..(offset + message.len())].copy_from_slice(message);
+
+    Ok(offset + message.len())
+}
+
+/// Encode `msg` as the advertising data of a legacy advertisement.
+///
+/// The Service Data AD fills all 31 bytes, so there is no room for a Flags AD. Remote ID
+/// advertisements are non-connectable and do not require one.
+pub fn legacy_advertising_data(
+    counter: u8,
+    msg: &RemoteIDMessage,
+    mode: EncodeMode,
+) -> Result<[u8; LEGACY_ADVERTISING_DATA_SIZE], EncodeError> {
+    let message = encode_message(msg, mode)?;
+
+    let mut data = [0u8; LEGACY_ADVERTISING_DATA_SIZE];
+    encode_advertising_data(&mut data, None, counter, &message)?;
+
+    Ok(data)
+}
+
+/// Encode `msgs` as a Message Pack in the advertising data of an extended advertisement,
+/// returning the number of bytes written.
+///
+/// Used with the LE Coded PHY (Long Range), the Flags AD is followed by the service data.
+pub fn extended_advertising_data(
+    buf: &mut [u8],
+    counter: u8,
+    msgs: &[RemoteIDMessage],
+    mode: EncodeMode,
+) -> Result<usize, EncodeError> {
+    let mut pack = [0u8; MAX_MESSAGE_PACK_SIZE];
+    let len = encode_message_pack(&mut pack, msgs, mode)?;
+
+    encode_advertising_data(buf, Some(DEFAULT_FLAGS), counter, &pack[..len])
+}
+
+/// Decode the Message Pack carried by the advertising data of an extended advertisement.
+///
+/// Returns the message counter and the pack, `None` when there is no Remote ID service data.
+pub fn decode_extended_advertising_data(
+    data: &[u8],
+) -> Result<Option<(u8, MessagePack<'_>)>, DecodeError> {
+    let Some(service_data) = find_service_data(data) else {
+        return Ok(None);
+    };
+
+    // app code and counter
+    if service_data.len() < 2 {
+        return Err(DecodeError::BufferTooShort {
+            expected: 2,
+            actual: service_data.len(),
+        });
+    }
+
+    let pack = decode_message_pack(&service_data[2..])?;
+    Ok(Some((service_data[1], pack)))
+}
+
+/// Decode the Remote ID service data of legacy or extended advertising data.
+///
+/// Returns the message counter along with the single message or Message Pack, `None` when
+/// there is no Remote ID service data.
+pub fn decode_advertising_data(data: &[u8]) -> Result<Option<(u8, Payload<'_>)>, DecodeError> {
+    let Some(service_data) = find_service_data(data) else {
+        return Ok(None);
+    };
+
+    // app code, counter and at least the message header
+    let Some(message) = service_data.get(2..).filter(|m| !m.is_empty()) else {
+        return Err(DecodeError::BufferTooShort {
+            expected: 3,
+            actual: service_data.len(),
+        });
+    };
+
+    let payload = match MessageKind::try_from(get_bits!(message[0], 7..4)) {
+        Ok(MessageKind::MessagePack) => Payload::Pack(decode_message_pack(message)?),
+        _ => Payload::Message(from_message_buffer(message)?),
+    };
+
+    Ok(Some((service_data[1], payload)))
+}
+
+/// A single AD structure of advertising data
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdStructure<'a> {
+    pub ad_type: u8,
+    pub data: &'a [u8],
+}
+
+/// Iterator over the AD structures of advertising or scan response data.
+///
+/// Ends at the first zero length, which marks the unused rest of the data. A structure
+/// running past the end of the data yields an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct AdStructures<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AdStructures<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for AdStructures<'a> {
+    type Item = Result<AdStructure<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&length, rest) = self.data.split_first()?;
+        let length = length as usize;
+        if length == 0 {
+            self.data = &[];
+            return None;
+        }
+
+        if rest.len() < length {
+            self.data = &[];
+            return Some(Err(DecodeError::BufferTooShort {
+                expected: length,
+                actual: rest.len(),
+            }));
+        }
+
+        let (structure, rest) = rest.split_at(length);
+        self.data = rest;
+
+        Some(Ok(AdStructure {
+            ad_type: structure[0],
+            data: &structure[1..],
+        }))
+    }
+}
+
+/// Find the Remote ID service data in advertising data.
+///
+/// Returns the bytes after the UUID, app code first, ready for
+/// [`from_service_data`](crate::codec::decode::from_service_data) or, for extended
+/// advertisements carrying a Message Pack, `decode_message_pack(&data[2..])`.
+///
+/// Structures are read up to the first malformed one, advertising data that is malformed
+/// ahead of the Remote ID service data has none.
+pub fn find_service_data(data: &[u8]) -> Option<&[u8]> {
+    for structure in AdStructures::new(data) {
+        let Ok(structure) = structure else {
+            break;
+        };
+        if structure.ad_type != SERVICE_DATA_AD_TYPE || structure.data.len() < 2 {
+            continue;
+        }
+
+        let (uuid, service_data) = structure.data.split_at(2);
+        if u16::from_le_bytes([uuid[0], uuid[1]]) == REMOTE_ID_SERVICE_UUID_16
+            && service_data.first() == Some(&OPEN_DRONE_ID_AD_CODE)
+        {
+            return Some(service_data);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::codec::decode::{from_message_buffer, from_service_data};
+    use crate::codec::MAX_MESSAGES_IN_PACK;
+    use crate::data::fixtures::basic_id;
+
+    #[test]
+    fn encode_legacy_advertising_data() {
+        let data = legacy_advertising_data(7, &basic_id(), EncodeMode::Strict).unwrap();
+
+        let expected = [
+            0x1E, 0x16, 0xFA, 0xFF, 0x0D, 7, 2, 16, 49, 53, 57, 54, 70, 51, 53, 57, 55, 52, 54, 49,
+            54, 55, 50, 54, 48, 55, 52, 57, 0, 0, 0,
+        ];
+        assert_eq!(expected, data);
+    }
+
+    #[test]
+    fn encode_with_flags() {
+        let message = encode_message(&basic_id(), EncodeMode::Strict).unwrap();
+
+        let mut buf = [0u8; 34];
+        assert_eq!(
+            Err(EncodeError::BufferTooSmall {
+                expected: 34,
+                actual: 31
+            }),
+            encode_advertising_data(&mut buf[..31], Some(DEFAULT_FLAGS), 0, &message)
+        );
+
+        let len = encode_advertising_data(&mut buf, Some(DEFAULT_FLAGS), 0, &message).unwrap();
+        assert_eq!(34, len);
+        assert_eq!([2, FLAGS_AD_TYPE, DEFAULT_FLAGS, 0x1E, 0x16], buf[..5]);
+    }
+
+    #[test]
+    fn find_among_other_structures() {
+        let message = legacy_advertising_data(3, &basic_id(), EncodeMode::Strict).unwrap();
+
+        // Flags, a complete local name, service data of another UUID, then Remote ID
+        let mut adv = std::vec![2, 0x01, 0x06, 4, 0x09, b'o', b'd', b'i', 4, 0x16, 0x0F, 0x18, 1];
+        adv.extend_from_slice(&message);
+        adv.extend_from_slice(&[0, 0, 0]);
+
+        let structures: std::vec::Vec<_> = AdStructures::new(&adv).collect();
+        assert_eq!(4, structures.len());
+        assert_eq!(
+            Ok(AdStructure {
+                ad_type: 0x09,
+                data: b"odi"
+            }),
+            structures[1]
+        );
+
+        let service_data = find_service_data(&adv).unwrap();
+        assert_eq!(2 + 25, service_data.len());
+        assert_eq!(3, service_data[1]);
+        assert_eq!(Ok(basic_id()), from_service_data(service_data));
+    }
+
+    #[test]
+    fn malformed_structures() {
+        assert_eq!(None, find_service_data(&[]));
+        assert_eq!(None, find_service_data(&[2, 0x01, 0x06]));
+        assert_eq!(None, find_service_data(&[2, 0x01, 0x06, 5, 0x16, 0xFA]));
+        // too short to hold a UUID
+        assert_eq!(None, find_service_data(&[2, 0x16, 0xFA]));
+
+        // a truncated local name ahead of the Remote ID service data
+        let message = legacy_advertising_data(3, &basic_id(), EncodeMode::Strict).unwrap();
+        let mut adv = std::vec![9, 0x09, b'o', b'd', b'i'];
+        adv.extend_from_slice(&message);
+        assert_eq!(
+            Ok(false),
+            decode_advertising_data(&adv).map(|d| d.is_some())
+        );
+
+        // Remote ID service data cut short
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 3,
+                actual: 2
+            }),
+            decode_advertising_data(&[5, 0x16, 0xFA, 0xFF, 0x0D, 0]).map(|d| d.is_some())
+        );
+    }
+
+    #[test]
+    fn extended_advertising_round_trip() {
+        let msgs: [RemoteIDMessage; MAX_MESSAGES_IN_PACK] = core::array::from_fn(|_| basic_id());
+        let mut counter = MessageCounter::new(u8::MAX);
+
+        let mut buf = [0u8; MAX_EXTENDED_ADVERTISING_DATA_SIZE];
+        let len = extended_advertising_data(&mut buf, counter.advance(), &msgs, EncodeMode::Strict)
+            .unwrap();
+        assert_eq!(MAX_EXTENDED_ADVERTISING_DATA_SIZE, len);
+        assert!(len <= 255);
+        assert_eq!(
+            [
+                2,
+                FLAGS_AD_TYPE,
+                DEFAULT_FLAGS,
+                233,
+                0x16,
+                0xFA,
+                0xFF,
+                0x0D,
+                255,
+                0xF2,
+                25,
+                9
+            ],
+            buf[..12]
+        );
+
+        let (count, pack) = decode_extended_advertising_data(&buf[..len])
+            .unwrap()
+            .unwrap();
+        assert_eq!(255, count);
+        assert_eq!(9, pack.len());
+        for msg in pack {
+            assert_eq!(Ok(basic_id()), msg);
+        }
+
+        let len =
+            extended_advertising_data(&mut buf, counter.advance(), &msgs[..2], EncodeMode::Strict)
+                .unwrap();
+        let (count, pack) = decode_extended_advertising_data(&buf[..len])
+            .unwrap()
+            .unwrap();
+        assert_eq!(0, count);
+        assert_eq!(2, pack.len());
+    }
+
+    #[test]
+    fn extended_advertising_errors() {
+        let msgs: [RemoteIDMessage; 10] = core::array::from_fn(|_| basic_id());
+        let mut buf = [0u8; 255];
+        assert_eq!(
+            Err(EncodeError::TooManyMessages(10)),
+            extended_advertising_data(&mut buf, 0, &msgs, EncodeMode::Strict)
+        );
+
+        // a legacy advertisement carries a single message, not a pack
+        let legacy = legacy_advertising_data(0, &basic_id(), EncodeMode::Strict).unwrap();
+        assert_eq!(
+            Err(DecodeError::UnknownMessageType(0)),
+            decode_extended_advertising_data(&legacy).map(|_| ())
+        );
+        assert!(from_message_buffer(&legacy[6..]).is_ok());
+
+        assert!(decode_extended_advertising_data(&[2, 0x01, 0x06])
+            .unwrap()
+            .is_none());
+
+        // pack with a count above nine
+        let len = encode_advertising_data(&mut buf, None, 0, &[0xF2, 25, 10]).unwrap();
+        assert_eq!(
+            Err(DecodeError::FieldOutOfRange {
+                field: "message count",
+                value: 10
+            }),
+            decode_extended_advertising_data(&buf[..len]).map(|_| ())
+        );
+    }
+}
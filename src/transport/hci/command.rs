@@ -0,0 +1,579 @@
+//! HCI command packets setting up a controller to broadcast Remote ID.
+//!
+//! Every function writes a single command packet, without the UART packet indicator
+//! ([`H4_COMMAND`](super::H4_COMMAND)), and returns the number of bytes written. Commands are
+//! sent one at a time, each one answered by a Command Complete event.
+//!
+//! Controllers with only the legacy advertising commands broadcast one advertisement, so the
+//! host rotates through the messages with [`LegacyRotation`]. Controllers supporting extended
+//! advertising keep several [`AdvertisingSet`]s, one per message broadcast in legacy PDUs,
+//! rotating through them on their own, or a single one broadcasting a Message Pack.
+
+use crate::codec::encode::{EncodeError, EncodeMode};
+use crate::codec::MAX_MESSAGES_IN_PACK;
+use crate::data::RemoteIDMessage;
+use crate::transport::bluetooth::{
+    extended_advertising_data, legacy_advertising_data, MessageCounter,
+    LEGACY_ADVERTISING_DATA_SIZE, MAX_EXTENDED_ADVERTISING_DATA_SIZE,
+};
+
+pub const OPCODE_LE_SET_RANDOM_ADDRESS: u16 = 0x2005;
+pub const OPCODE_LE_SET_ADVERTISING_PARAMETERS: u16 = 0x2006;
+pub const OPCODE_LE_SET_ADVERTISING_DATA: u16 = 0x2008;
+pub const OPCODE_LE_SET_ADVERTISING_ENABLE: u16 = 0x200A;
+pub const OPCODE_LE_SET_ADVERTISING_SET_RANDOM_ADDRESS: u16 = 0x2035;
+pub const OPCODE_LE_SET_EXTENDED_ADVERTISING_PARAMETERS: u16 = 0x2036;
+pub const OPCODE_LE_SET_EXTENDED_ADVERTISING_DATA: u16 = 0x2037;
+pub const OPCODE_LE_SET_EXTENDED_ADVERTISING_ENABLE: u16 = 0x2039;
+pub const OPCODE_LE_REMOVE_ADVERTISING_SET: u16 = 0x203C;
+pub const OPCODE_LE_CLEAR_ADVERTISING_SETS: u16 = 0x203D;
+
+/// Opcode and parameter length in front of the command parameters
+const COMMAND_HEADER_SIZE: usize = 2 + 1;
+
+/// Size of the largest command packet
+pub const MAX_COMMAND_SIZE: usize = COMMAND_HEADER_SIZE + u8::MAX as usize;
+
+/// Most advertising data a single LE Set Extended Advertising Data command carries
+pub const MAX_EXTENDED_ADVERTISING_DATA_FRAGMENT: usize = 251;
+
+/// Advertising type of non-connectable undirected advertisements (ADV_NONCONN_IND)
+pub const ADV_NONCONN_IND: u8 = 0x03;
+
+pub const OWN_ADDRESS_PUBLIC: u8 = 0x00;
+pub const OWN_ADDRESS_RANDOM: u8 = 0x01;
+
+/// Advertising on channels 37, 38 and 39
+pub const ALL_CHANNELS: u8 = 0x07;
+
+pub const PHY_LE_1M: u8 = 0x01;
+pub const PHY_LE_CODED: u8 = 0x03;
+
+/// Advertising event properties of an advertising set sending legacy ADV_NONCONN_IND PDUs
+pub const PROPERTIES_LEGACY_NONCONN: u16 = 0x0010;
+
+/// Advertising event properties of non-connectable, non-scannable extended advertising
+pub const PROPERTIES_EXTENDED_NONCONN: u16 = 0x0000;
+
+/// Advertising interval of 100 ms, in units of 0.625 ms
+const DEFAULT_INTERVAL: u16 = 160;
+
+/// Transmit power value leaving the choice to the controller
+const TX_POWER_NO_PREFERENCE: i8 = 127;
+
+/// Most advertising sets a single LE Set Extended Advertising Enable command handles
+const MAX_ENABLED_SETS: usize = 63;
+
+/// Parameters of the legacy advertisement, non-connectable and undirected
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdvertisingParameters {
+    /// Shortest advertising interval, in units of 0.625 ms
+    pub interval_min: u16,
+    /// Longest advertising interval, in units of 0.625 ms
+    pub interval_max: u16,
+    pub own_address_type: u8,
+    pub channel_map: u8,
+}
+
+impl Default for AdvertisingParameters {
+    fn default() -> Self {
+        Self {
+            interval_min: DEFAULT_INTERVAL,
+            interval_max: DEFAULT_INTERVAL,
+            own_address_type: OWN_ADDRESS_RANDOM,
+            channel_map: ALL_CHANNELS,
+        }
+    }
+}
+
+/// Parameters of an advertising set
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ExtendedAdvertisingParameters {
+    /// Advertising event properties, e.g. [`PROPERTIES_LEGACY_NONCONN`]
+    pub properties: u16,
+    /// Shortest advertising interval, in units of 0.625 ms, 24 bits wide
+    pub interval_min: u32,
+    /// Longest advertising interval, in units of 0.625 ms, 24 bits wide
+    pub interval_max: u32,
+    pub channel_map: u8,
+    pub own_address_type: u8,
+    /// Transmit power in dBm, `None` leaving it to the controller
+    pub tx_power: Option<i8>,
+    /// PHY of the advertisements on the primary channels
+    pub primary_phy: u8,
+    /// PHY of the auxiliary packets of extended advertisements
+    pub secondary_phy: u8,
+    /// Advertising SID, telling the sets of a device apart
+    pub sid: u8,
+}
+
+impl ExtendedAdvertisingParameters {
+    /// Legacy ADV_NONCONN_IND advertisements on the LE 1M PHY, each carrying a single message
+    pub fn legacy() -> Self {
+        Self {
+            properties: PROPERTIES_LEGACY_NONCONN,
+            interval_min: DEFAULT_INTERVAL as u32,
+            interval_max: DEFAULT_INTERVAL as u32,
+            channel_map: ALL_CHANNELS,
+            own_address_type: OWN_ADDRESS_RANDOM,
+            tx_power: None,
+            primary_phy: PHY_LE_1M,
+            secondary_phy: PHY_LE_1M,
+            sid: 0,
+        }
+    }
+
+    /// Extended advertisements on the LE Coded PHY (Long Range), carrying a Message Pack
+    pub fn long_range() -> Self {
+        Self {
+            properties: PROPERTIES_EXTENDED_NONCONN,
+            primary_phy: PHY_LE_CODED,
+            secondary_phy: PHY_LE_CODED,
+            ..Self::legacy()
+        }
+    }
+}
+
+/// Write a command packet, `parameters` filling in its `len` parameter bytes
+fn encode_command(
+    buf: &mut [u8],
+    opcode: u16,
+    len: usize,
+    parameters: impl FnOnce(&mut [u8]),
+) -> Result<usize, EncodeError> {
+    let expected = COMMAND_HEADER_SIZE + len;
+    if buf.len() < expected {
+        return Err(EncodeError::BufferTooSmall {
+            expected,
+            actual: buf.len(),
+        });
+    }
+
+    buf[..2].copy_from_slice(&opcode.to_le_bytes());
+    buf[2] = len as u8;
+    parameters(&mut buf[COMMAND_HEADER_SIZE..expected]);
+
+    Ok(expected)
+}
+
+/// Device addresses are sent least significant byte first
+fn put_address(buf: &mut [u8], address: &[u8; 6]) {
+    buf[..6].copy_from_slice(address);
+    buf[..6].reverse();
+}
+
+fn ensure_data_len(data: &[u8], max: usize) -> Result<(), EncodeError> {
+    if data.len() > max {
+        return Err(EncodeError::FieldOutOfRange {
+            field: "advertising data length",
+            value: data.len() as f64,
+        });
+    }
+
+    Ok(())
+}
+
+/// LE Set Random Address, `address` in the order it is usually written
+pub fn encode_set_random_address(buf: &mut [u8], address: &[u8; 6]) -> Result<usize, EncodeError> {
+    encode_command(buf, OPCODE_LE_SET_RANDOM_ADDRESS, 6, |p| {
+        put_address(p, address)
+    })
+}
+
+/// LE Set Advertising Parameters of a non-connectable, undirected advertisement
+pub fn encode_set_advertising_parameters(
+    buf: &mut [u8],
+    parameters: &AdvertisingParameters,
+) -> Result<usize, EncodeError> {
+    encode_command(buf, OPCODE_LE_SET_ADVERTISING_PARAMETERS, 15, |p| {
+        p[0..2].copy_from_slice(&parameters.interval_min.to_le_bytes());
+        p[2..4].copy_from_slice(&parameters.interval_max.to_le_bytes());
+        p[4] = ADV_NONCONN_IND;
+        p[5] = parameters.own_address_type;
+        // peer address type and address, unused by undirected advertising
+        p[6..13].fill(0);
+        p[13] = parameters.channel_map;
+        // filter policy, unused by non-connectable advertising
+        p[14] = 0;
+    })
+}
+
+/// LE Set Advertising Data, at most 31 bytes
+pub fn encode_set_advertising_data(buf: &mut [u8], data: &[u8]) -> Result<usize, EncodeError> {
+    ensure_data_len(data, LEGACY_ADVERTISING_DATA_SIZE)?;
+
+    // the data parameter is always 31 bytes, the unused rest zeroed
+    encode_command(
+        buf,
+        OPCODE_LE_SET_ADVERTISING_DATA,
+        1 + LEGACY_ADVERTISING_DATA_SIZE,
+        |p| {
+            p[0] = data.len() as u8;
+            p[1..(1 + data.len())].copy_from_slice(data);
+            p[(1 + data.len())..].fill(0);
+        },
+    )
+}
+
+/// LE Set Advertising Enable
+pub fn encode_set_advertising_enable(buf: &mut [u8], enable: bool) -> Result<usize, EncodeError> {
+    encode_command(buf, OPCODE_LE_SET_ADVERTISING_ENABLE, 1, |p| {
+        p[0] = enable as u8
+    })
+}
+
+/// LE Set Advertising Set Random Address, `address` in the order it is usually written
+pub fn encode_set_advertising_set_random_address(
+    buf: &mut [u8],
+    handle: u8,
+    address: &[u8; 6],
+) -> Result<usize, EncodeError> {
+    encode_command(buf, OPCODE_LE_SET_ADVERTISING_SET_RANDOM_ADDRESS, 7, |p| {
+        p[0] = handle;
+        put_address(&mut p[1..], address);
+    })
+}
+
+/// LE Set Extended Advertising Parameters, creating the advertising set if needed
+pub fn encode_set_extended_advertising_parameters(
+    buf: &mut [u8],
+    handle: u8,
+    parameters: &ExtendedAdvertisingParameters,
+) -> Result<usize, EncodeError> {
+    for (field, value) in [
+        ("advertising interval", parameters.interval_min),
+        ("advertising interval", parameters.interval_max),
+    ] {
+        if value > 0xFF_FFFF {
+            return Err(EncodeError::FieldOutOfRange {
+                field,
+                value: value as f64,
+            });
+        }
+    }
+
+    encode_command(
+        buf,
+        OPCODE_LE_SET_EXTENDED_ADVERTISING_PARAMETERS,
+        25,
+        |p| {
+            p[0] = handle;
+            p[1..3].copy_from_slice(&parameters.properties.to_le_bytes());
+            p[3..6].copy_from_slice(&parameters.interval_min.to_le_bytes()[..3]);
+            p[6..9].copy_from_slice(&parameters.interval_max.to_le_bytes()[..3]);
+            p[9] = parameters.channel_map;
+            p[10] = parameters.own_address_type;
+            // peer address type and address, filter policy
+            p[11..19].fill(0);
+            p[19] = parameters.tx_power.unwrap_or(TX_POWER_NO_PREFERENCE) as u8;
+            p[20] = parameters.primary_phy;
+            // secondary advertising max skip
+            p[21] = 0;
+            p[22] = parameters.secondary_phy;
+            p[23] = parameters.sid;
+            // scan request notifications
+            p[24] = 0;
+        },
+    )
+}
+
+/// LE Set Extended Advertising Data with the complete data of the set, at most 251 bytes
+pub fn encode_set_extended_advertising_data(
+    buf: &mut [u8],
+    handle: u8,
+    data: &[u8],
+) -> Result<usize, EncodeError> {
+    ensure_data_len(data, MAX_EXTENDED_ADVERTISING_DATA_FRAGMENT)?;
+
+    encode_command(
+        buf,
+        OPCODE_LE_SET_EXTENDED_ADVERTISING_DATA,
+        4 + data.len(),
+        |p| {
+            p[0] = handle;
+            // operation: complete data
+            p[1] = 0x03;
+            // fragment preference: the controller should not fragment
+            p[2] = 0x01;
+            p[3] = data.len() as u8;
+            p[4..].copy_from_slice(data);
+        },
+    )
+}
+
+/// LE Set Extended Advertising Enable for the sets of `handles`, advertising until disabled
+pub fn encode_set_extended_advertising_enable(
+    buf: &mut [u8],
+    enable: bool,
+    handles: &[u8],
+) -> Result<usize, EncodeError> {
+    if handles.len() > MAX_ENABLED_SETS {
+        return Err(EncodeError::FieldOutOfRange {
+            field: "number of advertising sets",
+            value: handles.len() as f64,
+        });
+    }
+
+    encode_command(
+        buf,
+        OPCODE_LE_SET_EXTENDED_ADVERTISING_ENABLE,
+        2 + 4 * handles.len(),
+        |p| {
+            p[0] = enable as u8;
+            p[1] = handles.len() as u8;
+            for (set, &handle) in p[2..].chunks_exact_mut(4).zip(handles) {
+                // handle, no duration and no limit on the number of events
+                set.copy_from_slice(&[handle, 0, 0, 0]);
+            }
+        },
+    )
+}
+
+/// LE Remove Advertising Set
+pub fn encode_remove_advertising_set(buf: &mut [u8], handle: u8) -> Result<usize, EncodeError> {
+    encode_command(buf, OPCODE_LE_REMOVE_ADVERTISING_SET, 1, |p| p[0] = handle)
+}
+
+/// LE Clear Advertising Sets, removing all of them
+pub fn encode_clear_advertising_sets(buf: &mut [u8]) -> Result<usize, EncodeError> {
+    encode_command(buf, OPCODE_LE_CLEAR_ADVERTISING_SETS, 0, |_| {})
+}
+
+/// Advertising set broadcasting Remote ID, keeping the message counter of what it sends
+#[derive(Debug, Clone)]
+pub struct AdvertisingSet {
+    handle: u8,
+    counter: MessageCounter,
+}
+
+impl AdvertisingSet {
+    pub const fn new(handle: u8) -> Self {
+        Self {
+            handle,
+            counter: MessageCounter::new(0),
+        }
+    }
+
+    pub fn handle(&self) -> u8 {
+        self.handle
+    }
+
+    /// LE Set Extended Advertising Parameters of the set
+    pub fn encode_parameters(
+        &self,
+        buf: &mut [u8],
+        parameters: &ExtendedAdvertisingParameters,
+    ) -> Result<usize, EncodeError> {
+        encode_set_extended_advertising_parameters(buf, self.handle, parameters)
+    }
+
+    /// LE Set Extended Advertising Data broadcasting `msg` in legacy PDUs, see
+    /// [`ExtendedAdvertisingParameters::legacy`]
+    pub fn encode_message(
+        &mut self,
+        buf: &mut [u8],
+        msg: &RemoteIDMessage,
+        mode: EncodeMode,
+    ) -> Result<usize, EncodeError> {
+        let data = legacy_advertising_data(self.counter.advance(), msg, mode)?;
+        encode_set_extended_advertising_data(buf, self.handle, &data)
+    }
+
+    /// LE Set Extended Advertising Data broadcasting `msgs` as a Message Pack in extended
+    /// advertisements, see [`ExtendedAdvertisingParameters::long_range`]
+    pub fn encode_message_pack(
+        &mut self,
+        buf: &mut [u8],
+        msgs: &[RemoteIDMessage],
+        mode: EncodeMode,
+    ) -> Result<usize, EncodeError> {
+        let mut data = [0u8; MAX_EXTENDED_ADVERTISING_DATA_SIZE];
+        let len = extended_advertising_data(&mut data, self.counter.advance(), msgs, mode)?;
+        encode_set_extended_advertising_data(buf, self.handle, &data[..len])
+    }
+}
+
+/// Rotation through the messages of a broadcast on controllers with a single, legacy
+/// advertisement.
+///
+/// The host replaces the advertising data with the next message once per advertising
+/// interval, so every message is broadcast in turn. Each message keeps its own counter,
+/// identified by its position in the rotation.
+#[derive(Debug, Default, Clone)]
+pub struct LegacyRotation {
+    counters: [MessageCounter; MAX_MESSAGES_IN_PACK],
+    next: usize,
+}
+
+impl LegacyRotation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// LE Set Advertising Data broadcasting the next of `msgs`, which holds up to nine
+    /// messages in the same order on every call
+    pub fn encode_next(
+        &mut self,
+        buf: &mut [u8],
+        msgs: &[RemoteIDMessage],
+        mode: EncodeMode,
+    ) -> Result<usize, EncodeError> {
+        if msgs.is_empty() {
+            return Err(EncodeError::NoMessages);
+        }
+        if msgs.len() > MAX_MESSAGES_IN_PACK {
+            return Err(EncodeError::TooManyMessages(msgs.len()));
+        }
+
+        let index = self.next % msgs.len();
+        let data = legacy_advertising_data(self.counters[index].advance(), &msgs[index], mode)?;
+        let len = encode_set_advertising_data(buf, &data)?;
+
+        self.next = (index + 1) % msgs.len();
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::data::fixtures::{basic_id, operator_id};
+
+    #[test]
+    fn legacy_commands() {
+        let mut buf = [0u8; MAX_COMMAND_SIZE];
+
+        let len = encode_set_random_address(&mut buf, &[0xC0, 0xFF, 0xEE, 0x12, 0x34, 0x56]);
+        assert_eq!(
+            [0x05, 0x20, 6, 0x56, 0x34, 0x12, 0xEE, 0xFF, 0xC0],
+            buf[..len.unwrap()]
+        );
+
+        let len = encode_set_advertising_parameters(&mut buf, &AdvertisingParameters::default());
+        assert_eq!(
+            [0x06, 0x20, 15, 0xA0, 0x00, 0xA0, 0x00, 3, 1, 0, 0, 0, 0, 0, 0, 0, 7, 0],
+            buf[..len.unwrap()]
+        );
+
+        let len = encode_set_advertising_enable(&mut buf, true);
+        assert_eq!([0x0A, 0x20, 1, 1], buf[..len.unwrap()]);
+
+        let len = encode_set_advertising_data(&mut buf, &[2, 1, 6]).unwrap();
+        assert_eq!(3 + 32, len);
+        assert_eq!([0x08, 0x20, 32, 3, 2, 1, 6, 0], buf[..8]);
+        assert!(buf[8..len].iter().all(|&b| b == 0));
+
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "advertising data length",
+                value: 32.0
+            }),
+            encode_set_advertising_data(&mut buf, &[0; 32])
+        );
+        assert_eq!(
+            Err(EncodeError::BufferTooSmall {
+                expected: 4,
+                actual: 3
+            }),
+            encode_set_advertising_enable(&mut buf[..3], false)
+        );
+    }
+
+    #[test]
+    fn extended_commands() {
+        let mut buf = [0u8; MAX_COMMAND_SIZE];
+
+        let parameters = ExtendedAdvertisingParameters {
+            tx_power: Some(-4),
+            sid: 2,
+            ..ExtendedAdvertisingParameters::long_range()
+        };
+        let len = encode_set_extended_advertising_parameters(&mut buf, 1, &parameters);
+        let expected = [
+            0x36, 0x20, 25, 1, 0x00, 0x00, 0xA0, 0x00, 0x00, 0xA0, 0x00, 0x00, 7, 1, 0, 0, 0, 0, 0,
+            0, 0, 0, 0xFC, 3, 0, 3, 2, 0,
+        ];
+        assert_eq!(expected, buf[..len.unwrap()]);
+
+        let parameters = ExtendedAdvertisingParameters {
+            interval_max: 0x100_0000,
+            ..ExtendedAdvertisingParameters::legacy()
+        };
+        assert!(encode_set_extended_advertising_parameters(&mut buf, 1, &parameters).is_err());
+
+        let len = encode_set_extended_advertising_enable(&mut buf, true, &[0, 1]);
+        assert_eq!(
+            [0x39, 0x20, 10, 1, 2, 0, 0, 0, 0, 1, 0, 0, 0],
+            buf[..len.unwrap()]
+        );
+
+        let len = encode_remove_advertising_set(&mut buf, 1);
+        assert_eq!([0x3C, 0x20, 1, 1], buf[..len.unwrap()]);
+        let len = encode_clear_advertising_sets(&mut buf);
+        assert_eq!([0x3D, 0x20, 0], buf[..len.unwrap()]);
+    }
+
+    #[test]
+    fn advertising_sets() {
+        let mut buf = [0u8; MAX_COMMAND_SIZE];
+
+        // one set per message, the controller rotating through them
+        let mut basic_id_set = AdvertisingSet::new(0);
+        let mut operator_id_set = AdvertisingSet::new(1);
+        for counter in 0..2 {
+            let len = basic_id_set
+                .encode_message(&mut buf, &basic_id(), EncodeMode::Strict)
+                .unwrap();
+            assert_eq!(3 + 4 + LEGACY_ADVERTISING_DATA_SIZE, len);
+            assert_eq!([0x37, 0x20, 35, 0, 3, 1, 31], buf[..7]);
+            // service data header, counter and message header
+            assert_eq!([0x1E, 0x16, 0xFA, 0xFF, 0x0D, counter, 0x02], buf[7..14]);
+        }
+
+        operator_id_set
+            .encode_message(&mut buf, &operator_id(), EncodeMode::Strict)
+            .unwrap();
+        assert_eq!([1, 3, 1, 31], buf[3..7]);
+        assert_eq!([0, 0x52], buf[12..14]);
+
+        // a single set broadcasting a Message Pack
+        let mut pack_set = AdvertisingSet::new(2);
+        let len = pack_set
+            .encode_message_pack(&mut buf, &[basic_id(), operator_id()], EncodeMode::Strict)
+            .unwrap();
+        let data_len = 3 + 1 + 5 + 3 + 2 * 25;
+        assert_eq!(3 + 4 + data_len, len);
+        assert_eq!(
+            [0x37, 0x20, 4 + data_len as u8, 2, 3, 1, data_len as u8],
+            buf[..7]
+        );
+    }
+
+    #[test]
+    fn legacy_rotation() {
+        let mut buf = [0u8; MAX_COMMAND_SIZE];
+        let msgs = [basic_id(), operator_id()];
+
+        // counter and message header of each advertisement
+        let mut rotation = LegacyRotation::new();
+        let mut sent = std::vec::Vec::new();
+        for _ in 0..5 {
+            let len = rotation
+                .encode_next(&mut buf, &msgs, EncodeMode::Strict)
+                .unwrap();
+            assert_eq!(35, len);
+            sent.push((buf[9], buf[10]));
+        }
+        assert_eq!(
+            std::vec![(0, 0x02), (0, 0x52), (1, 0x02), (1, 0x52), (2, 0x02)],
+            sent
+        );
+
+        assert_eq!(
+            Err(EncodeError::NoMessages),
+            rotation.encode_next(&mut buf, &[], EncodeMode::Strict)
+        );
+    }
+}
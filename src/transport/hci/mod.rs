@@ -0,0 +1,287 @@
+//! Bluetooth Host Controller Interface (HCI) packets.
+//!
+//! Scanning controllers hand received advertisements to the host in LE Advertising Report and
+//! LE Extended Advertising Report events. The advertising data of a report can be passed to
+//! [`decode_advertising_data`](super::bluetooth::decode_advertising_data). Broadcasting
+//! controllers are set up with the packets of [`command`].
+
+use crate::codec::decode::DecodeError;
+
+pub mod command;
+
+/// UART (H4) packet indicator in front of an HCI command packet
+pub const H4_COMMAND: u8 = 0x01;
+
+/// UART (H4) packet indicator in front of an HCI event packet
+pub const H4_EVENT: u8 = 0x04;
+
+/// Event code of the LE Meta event, the subevent code is its first parameter
+pub const EVENT_LE_META: u8 = 0x3E;
+
+pub const SUBEVENT_ADVERTISING_REPORT: u8 = 0x02;
+pub const SUBEVENT_EXTENDED_ADVERTISING_REPORT: u8 = 0x0D;
+
+/// Event code and parameter length in front of the event parameters
+const EVENT_HEADER_SIZE: usize = 2;
+
+/// Event type, address type, address and data length
+const ADVERTISING_REPORT_HEADER_SIZE: usize = 1 + 1 + 6 + 1;
+
+/// Event type, address type, address, PHYs, SID, TX power, RSSI, periodic advertising
+/// interval, direct address type, direct address and data length
+const EXTENDED_ADVERTISING_REPORT_HEADER_SIZE: usize =
+    2 + 1 + 6 + 1 + 1 + 1 + 1 + 1 + 2 + 1 + 6 + 1;
+
+/// RSSI value of a report when the controller could not measure it
+const RSSI_NOT_AVAILABLE: i8 = 127;
+
+/// A single advertisement reported by the controller
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdvertisingReport<'a> {
+    /// Event type, 0-4 (ADV_IND to SCAN_RSP) for legacy reports and a bit field for extended
+    /// ones
+    pub event_type: u16,
+    /// Set for reports of an LE Extended Advertising Report event
+    pub extended: bool,
+    /// 0: public, 1: random, 2-3: identity addresses resolved by the controller
+    pub address_type: u8,
+    /// Address of the advertiser, in the order it is usually written
+    pub address: [u8; 6],
+    /// Received signal strength in dBm
+    pub rssi: Option<i8>,
+    /// Advertising data
+    pub data: &'a [u8],
+}
+
+impl AdvertisingReport<'_> {
+    /// Whether the report holds all of the advertising data. Controllers split long extended
+    /// advertising data over several reports, the earlier ones flagged as incomplete.
+    pub fn is_complete(&self) -> bool {
+        // data status, 0: complete, 1: more to come, 2: truncated
+        !self.extended || (self.event_type >> 5) & 0b11 == 0
+    }
+}
+
+/// Iterator over the reports of an LE Advertising Report or LE Extended Advertising Report
+/// event.
+///
+/// A report running past the end of the event yields an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct AdvertisingReports<'a> {
+    data: &'a [u8],
+    remaining: u8,
+    extended: bool,
+}
+
+/// Parse the advertising reports of an HCI event packet.
+///
+/// `event` starts with the event code, without the packet indicator of a UART transport.
+/// Returns `None` for events other than advertising reports.
+/// ```
+/// # use remote_id::transport::hci::advertising_reports;
+/// // LE Advertising Report, one ADV_NONCONN_IND without data at -60 dBm
+/// let event = [0x3E, 0x0C, 0x02, 1, 3, 1, 0x56, 0x34, 0x12, 0xEE, 0xFF, 0xC0, 0, 0xC4];
+/// let report = advertising_reports(&event).unwrap().unwrap().next().unwrap().unwrap();
+/// assert_eq!([0xC0, 0xFF, 0xEE, 0x12, 0x34, 0x56], report.address);
+/// assert_eq!(Some(-60), report.rssi);
+/// ```
+pub fn advertising_reports(event: &[u8]) -> Result<Option<AdvertisingReports<'_>>, DecodeError> {
+    if event.len() < EVENT_HEADER_SIZE {
+        return Err(DecodeError::BufferTooShort {
+            expected: EVENT_HEADER_SIZE,
+            actual: event.len(),
+        });
+    }
+    if event[0] != EVENT_LE_META {
+        return Ok(None);
+    }
+
+    let parameters = &event[EVENT_HEADER_SIZE..];
+    let len = event[1] as usize;
+    if parameters.len() < len.max(2) {
+        return Err(DecodeError::BufferTooShort {
+            expected: len.max(2),
+            actual: parameters.len(),
+        });
+    }
+
+    let extended = match parameters[0] {
+        SUBEVENT_ADVERTISING_REPORT => false,
+        SUBEVENT_EXTENDED_ADVERTISING_REPORT => true,
+        _ => return Ok(None),
+    };
+
+    Ok(Some(AdvertisingReports {
+        data: &parameters[2..len.max(2)],
+        remaining: parameters[1],
+        extended,
+    }))
+}
+
+impl<'a> AdvertisingReports<'a> {
+    fn split(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let Some((head, rest)) = self.data.split_at_checked(len) else {
+            let actual = self.data.len();
+            self.data = &[];
+            self.remaining = 0;
+            return Err(DecodeError::BufferTooShort {
+                expected: len,
+                actual,
+            });
+        };
+
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn next_report(&mut self) -> Result<AdvertisingReport<'a>, DecodeError> {
+        let report = if self.extended {
+            let header = self.split(EXTENDED_ADVERTISING_REPORT_HEADER_SIZE)?;
+            let data = self.split(header[23] as usize)?;
+
+            AdvertisingReport {
+                event_type: u16::from_le_bytes([header[0], header[1]]),
+                extended: true,
+                address_type: header[2],
+                address: address(&header[3..9]),
+                rssi: rssi(header[13]),
+                data,
+            }
+        } else {
+            // the RSSI follows the data
+            let header = self.split(ADVERTISING_REPORT_HEADER_SIZE)?;
+            let data = self.split(header[8] as usize)?;
+            let rssi_byte = self.split(1)?[0];
+
+            AdvertisingReport {
+                event_type: header[0] as u16,
+                extended: false,
+                address_type: header[1],
+                address: address(&header[2..8]),
+                rssi: rssi(rssi_byte),
+                data,
+            }
+        };
+
+        Ok(report)
+    }
+}
+
+impl<'a> Iterator for AdvertisingReports<'a> {
+    type Item = Result<AdvertisingReport<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        Some(self.next_report())
+    }
+}
+
+/// Device addresses are sent least significant byte first
+fn address(bytes: &[u8]) -> [u8; 6] {
+    let mut address: [u8; 6] = bytes.try_into().unwrap();
+    address.reverse();
+    address
+}
+
+fn rssi(value: u8) -> Option<i8> {
+    Some(value as i8).filter(|&rssi| rssi != RSSI_NOT_AVAILABLE)
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::codec::encode::EncodeMode;
+    use crate::data::fixtures::basic_id;
+    use crate::transport::bluetooth::{decode_advertising_data, legacy_advertising_data};
+    use crate::transport::Payload;
+
+    #[test]
+    fn legacy_report() {
+        let data = legacy_advertising_data(3, &basic_id(), EncodeMode::Strict).unwrap();
+
+        // two reports, the second one without data or RSSI
+        let mut event = std::vec![EVENT_LE_META, 0, SUBEVENT_ADVERTISING_REPORT, 2];
+        event.extend([3, 1, 0x56, 0x34, 0x12, 0xEE, 0xFF, 0xC0, 31]);
+        event.extend(data);
+        event.push(-72i8 as u8);
+        event.extend([4, 0, 1, 2, 3, 4, 5, 6, 0, 127]);
+        event[1] = (event.len() - 2) as u8;
+
+        let reports: Vec<_> = advertising_reports(&event)
+            .unwrap()
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(2, reports.len());
+
+        assert_eq!(3, reports[0].event_type);
+        assert_eq!(1, reports[0].address_type);
+        assert_eq!([0xC0, 0xFF, 0xEE, 0x12, 0x34, 0x56], reports[0].address);
+        assert_eq!(Some(-72), reports[0].rssi);
+        assert!(reports[0].is_complete());
+        assert!(matches!(
+            decode_advertising_data(reports[0].data),
+            Ok(Some((3, Payload::Message(message)))) if message == basic_id()
+        ));
+
+        assert_eq!([6, 5, 4, 3, 2, 1], reports[1].address);
+        assert_eq!(None, reports[1].rssi);
+        assert!(reports[1].data.is_empty());
+    }
+
+    #[test]
+    fn extended_report() {
+        let data = [2, 1, 6];
+
+        let mut event = std::vec![EVENT_LE_META, 0, SUBEVENT_EXTENDED_ADVERTISING_REPORT, 1];
+        // connectable, data status "more to come"
+        event.extend([0x21, 0x00, 0, 1, 2, 3, 4, 5, 6]);
+        // LE 1M and LE Coded PHY, SID 0, TX power and RSSI
+        event.extend([1, 3, 0, 127, -80i8 as u8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        event.push(data.len() as u8);
+        event.extend(data);
+        event[1] = (event.len() - 2) as u8;
+
+        let report = advertising_reports(&event)
+            .unwrap()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(report.extended);
+        assert_eq!(0x21, report.event_type);
+        assert!(!report.is_complete());
+        assert_eq!([6, 5, 4, 3, 2, 1], report.address);
+        assert_eq!(Some(-80), report.rssi);
+        assert_eq!(data, report.data);
+    }
+
+    #[test]
+    fn truncated_report() {
+        // the report claims 31 bytes of data
+        let event = [EVENT_LE_META, 12, 2, 1, 0, 0, 1, 2, 3, 4, 5, 6, 31, 0];
+        let mut reports = advertising_reports(&event).unwrap().unwrap();
+        assert_eq!(
+            Some(Err(DecodeError::BufferTooShort {
+                expected: 31,
+                actual: 1
+            })),
+            reports.next()
+        );
+        assert_eq!(None, reports.next());
+
+        // other events are skipped, Command Complete
+        assert_eq!(
+            None,
+            advertising_reports(&[0x0E, 1, 1]).unwrap().map(|_| ())
+        );
+    }
+}
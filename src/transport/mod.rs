@@ -0,0 +1,15 @@
+use crate::codec::decode::MessagePack;
+use crate::data::RemoteIDMessage;
+
+pub mod bluetooth;
+pub mod hci;
+pub mod wifi;
+
+/// Remote ID content of a broadcast
+#[derive(Debug, Clone)]
+pub enum Payload<'a> {
+    /// A single message, sent in a legacy Bluetooth advertisement
+    Message(RemoteIDMessage),
+    /// A Message Pack, sent in an extended Bluetooth advertisement or over Wi-Fi
+    Pack(MessagePack<'a>),
+}
@@ -0,0 +1,374 @@
+use crate::codec::decode::{decode_message_pack, DecodeError, MessagePack};
+use crate::codec::encode::{encode_message_pack, EncodeError, EncodeMode};
+use crate::codec::{MAX_MESSAGES_IN_PACK, MAX_MESSAGE_PACK_SIZE, MESSAGE_SIZE};
+use crate::data::RemoteIDMessage;
+
+use super::{
+    decode_management_header, encode_management_header, InformationElement, InformationElements,
+    ManagementHeader, RemoteIdFrame, MANAGEMENT_HEADER_SIZE, SUBTYPE_BEACON,
+};
+
+/// OUI of ASTM International
+pub const ASTM_OUI: [u8; 3] = [0xFA, 0x0B, 0xBC];
+
+/// Vendor specific type of Remote ID elements
+pub const VENDOR_TYPE_REMOTE_ID: u8 = 0x0D;
+
+pub const ELEMENT_SSID: u8 = 0;
+pub const ELEMENT_SUPPORTED_RATES: u8 = 1;
+pub const ELEMENT_VENDOR_SPECIFIC: u8 = 0xDD;
+
+/// Longest SSID an SSID element can hold
+pub const MAX_SSID_SIZE: usize = 32;
+
+/// Timestamp, beacon interval and capability information
+const FIXED_FIELDS_SIZE: usize = 8 + 2 + 2;
+
+/// OUI, vendor type and message counter in front of the Message Pack
+const VENDOR_HEADER_SIZE: usize = 3 + 1 + 1;
+
+/// 6 Mbit/s, basic rate
+const SUPPORTED_RATES: [u8; 3] = [ELEMENT_SUPPORTED_RATES, 1, 0x8C];
+
+/// Size of the Remote ID vendor specific element carrying a full Message Pack
+pub const MAX_VENDOR_ELEMENT_SIZE: usize = 2 + VENDOR_HEADER_SIZE + MAX_MESSAGE_PACK_SIZE;
+
+/// Size of a beacon frame with the longest SSID and a full Message Pack
+pub const MAX_FRAME_SIZE: usize = MANAGEMENT_HEADER_SIZE
+    + FIXED_FIELDS_SIZE
+    + 2
+    + MAX_SSID_SIZE
+    + SUPPORTED_RATES.len()
+    + MAX_VENDOR_ELEMENT_SIZE;
+
+/// Fixed fields and SSID of a beacon frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Beacon<'a> {
+    /// MAC address of the transmitter
+    pub source: [u8; 6],
+    pub bssid: [u8; 6],
+    /// Microseconds of the transmitter's timer, usually filled in by the hardware
+    pub timestamp: u64,
+    /// Time between beacons in units of 1024 µs
+    pub beacon_interval: u16,
+    pub capability: u16,
+    pub ssid: &'a [u8],
+}
+
+/// Write the Remote ID vendor specific element holding `msgs` as a Message Pack, returning
+/// the number of bytes written
+pub fn encode_vendor_element(
+    buf: &mut [u8],
+    counter: u8,
+    msgs: &[RemoteIDMessage],
+    mode: EncodeMode,
+) -> Result<usize, EncodeError> {
+    let mut pack = [0u8; MAX_MESSAGE_PACK_SIZE];
+    let pack_len = encode_message_pack(&mut pack, msgs, mode)?;
+
+    let length = VENDOR_HEADER_SIZE + pack_len;
+    let expected = 2 + length;
+    if buf.len() < expected {
+        return Err(EncodeError::BufferTooSmall {
+            expected,
+            actual: buf.len(),
+        });
+    }
+
+    buf[0] = ELEMENT_VENDOR_SPECIFIC;
+    buf[1] = length as u8;
+    buf[2..5].copy_from_slice(&ASTM_OUI);
+    buf[5] = VENDOR_TYPE_REMOTE_ID;
+    buf[6] = counter;
+    buf[7..expected].copy_from_slice(&pack[..pack_len]);
+
+    Ok(expected)
+}
+
+/// Decode a Remote ID vendor specific element into its message counter and Message Pack,
+/// `None` for any other element
+pub fn decode_vendor_element<'a>(
+    element: &InformationElement<'a>,
+) -> Result<Option<(u8, MessagePack<'a>)>, DecodeError> {
+    let body = element.body;
+    if element.id != ELEMENT_VENDOR_SPECIFIC
+        || body.len() < 4
+        || body[..3] != ASTM_OUI
+        || body[3] != VENDOR_TYPE_REMOTE_ID
+    {
+        return Ok(None);
+    }
+
+    if body.len() < VENDOR_HEADER_SIZE {
+        return Err(DecodeError::BufferTooShort {
+            expected: VENDOR_HEADER_SIZE,
+            actual: body.len(),
+        });
+    }
+
+    let pack = decode_message_pack(&body[VENDOR_HEADER_SIZE..])?;
+    Ok(Some((body[4], pack)))
+}
+
+/// Build a beacon frame carrying `msgs` as a Message Pack, returning the number of bytes
+/// written.
+///
+/// The SSID is followed by a Supported Rates element and the Remote ID vendor specific
+/// element. The FCS is left to the hardware.
+pub fn encode_beacon_frame(
+    buf: &mut [u8],
+    beacon: &Beacon,
+    counter: u8,
+    msgs: &[RemoteIDMessage],
+    mode: EncodeMode,
+) -> Result<usize, EncodeError> {
+    if beacon.ssid.len() > MAX_SSID_SIZE {
+        return Err(EncodeError::FieldOutOfRange {
+            field: "ssid length",
+            value: beacon.ssid.len() as f64,
+        });
+    }
+
+    if msgs.len() > MAX_MESSAGES_IN_PACK {
+        return Err(EncodeError::TooManyMessages(msgs.len()));
+    }
+
+    let expected = MANAGEMENT_HEADER_SIZE
+        + FIXED_FIELDS_SIZE
+        + 2
+        + beacon.ssid.len()
+        + SUPPORTED_RATES.len()
+        + 2
+        + VENDOR_HEADER_SIZE
+        + 3
+        + msgs.len() * MESSAGE_SIZE;
+    if buf.len() < expected {
+        return Err(EncodeError::BufferTooSmall {
+            expected,
+            actual: buf.len(),
+        });
+    }
+
+    let header = ManagementHeader {
+        subtype: SUBTYPE_BEACON,
+        destination: [0xFF; 6],
+        source: beacon.source,
+        bssid: beacon.bssid,
+    };
+    let mut offset = encode_management_header(buf, &header)?;
+
+    buf[offset..(offset + 8)].copy_from_slice(&beacon.timestamp.to_le_bytes());
+    buf[(offset + 8)..(offset + 10)].copy_from_slice(&beacon.beacon_interval.to_le_bytes());
+    buf[(offset + 10)..(offset + 12)].copy_from_slice(&beacon.capability.to_le_bytes());
+    offset += FIXED_FIELDS_SIZE;
+
+    buf[offset] = ELEMENT_SSID;
+    buf[offset + 1] = beacon.ssid.len() as u8;
+    buf[(offset + 2)..(offset + 2 + beacon.ssid.len())].copy_from_slice(beacon.ssid);
+    offset += 2 + beacon.ssid.len();
+
+    buf[offset..(offset + SUPPORTED_RATES.len())].copy_from_slice(&SUPPORTED_RATES);
+    offset += SUPPORTED_RATES.len();
+
+    offset += encode_vendor_element(&mut buf[offset..], counter, msgs, mode)?;
+
+    Ok(offset)
+}
+
+/// Parse the fixed fields and SSID of a beacon frame, along with all of its elements.
+///
+/// Returns `None` for frames other than beacons. The SSID is left empty when a malformed
+/// element comes first, the returned elements still yield the error.
+pub fn parse_beacon_frame(
+    frame: &[u8],
+) -> Result<Option<(Beacon<'_>, InformationElements<'_>)>, DecodeError> {
+    let Some(header) = decode_management_header(frame)? else {
+        return Ok(None);
+    };
+    if header.subtype != SUBTYPE_BEACON {
+        return Ok(None);
+    }
+
+    let body = &frame[MANAGEMENT_HEADER_SIZE..];
+    if body.len() < FIXED_FIELDS_SIZE {
+        return Err(DecodeError::BufferTooShort {
+            expected: FIXED_FIELDS_SIZE,
+            actual: body.len(),
+        });
+    }
+
+    let elements = InformationElements::new(&body[FIXED_FIELDS_SIZE..]);
+    let mut ssid: &[u8] = &[];
+    for element in elements.clone() {
+        let Ok(element) = element else {
+            break;
+        };
+        if element.id == ELEMENT_SSID {
+            ssid = element.body;
+            break;
+        }
+    }
+
+    let beacon = Beacon {
+        source: header.source,
+        bssid: header.bssid,
+        timestamp: u64::from_le_bytes(body[0..8].try_into().unwrap()),
+        beacon_interval: u16::from_le_bytes([body[8], body[9]]),
+        capability: u16::from_le_bytes([body[10], body[11]]),
+        ssid,
+    };
+
+    Ok(Some((beacon, elements)))
+}
+
+/// Decode the Remote ID broadcast carried by an 802.11 frame.
+///
+/// `frame` starts with the 802.11 header and ends after the last element, the caller strips
+/// the FCS. Returns `None` for frames that are not beacons or beacons without a Remote ID
+/// element. Elements are read up to the first malformed one, only errors of the Remote ID
+/// element itself are returned.
+pub fn decode_beacon_frame(frame: &[u8]) -> Result<Option<RemoteIdFrame<'_>>, DecodeError> {
+    let Some((beacon, elements)) = parse_beacon_frame(frame)? else {
+        return Ok(None);
+    };
+
+    for element in elements {
+        let Ok(element) = element else {
+            break;
+        };
+        if let Some((counter, pack)) = decode_vendor_element(&element)? {
+            return Ok(Some(RemoteIdFrame {
+                source: beacon.source,
+                counter,
+                pack,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::data::fixtures::self_id;
+
+    fn beacon() -> Beacon<'static> {
+        Beacon {
+            source: [0x60, 0x60, 0x1F, 0x01, 0x02, 0x03],
+            bssid: [0x60, 0x60, 0x1F, 0x01, 0x02, 0x03],
+            timestamp: 0,
+            beacon_interval: 100,
+            capability: 0x0421,
+            ssid: b"UAS-1596F359746167260749",
+        }
+    }
+
+    #[test]
+    fn encode_element() {
+        let mut buf = [0u8; MAX_VENDOR_ELEMENT_SIZE];
+        let len = encode_vendor_element(&mut buf, 9, &[self_id()], EncodeMode::Strict).unwrap();
+        assert_eq!(2 + 5 + 3 + 25, len);
+        assert_eq!(
+            [0xDD, 33, 0xFA, 0x0B, 0xBC, 0x0D, 9, 0xF2, 25, 1, 0x32, 0],
+            buf[..12]
+        );
+        assert_eq!(b"Bridge survey", &buf[12..25]);
+    }
+
+    #[test]
+    fn beacon_round_trip() {
+        let msgs: [RemoteIDMessage; MAX_MESSAGES_IN_PACK] = core::array::from_fn(|_| self_id());
+        let mut buf = [0u8; MAX_FRAME_SIZE + 4];
+        let len = encode_beacon_frame(&mut buf, &beacon(), 17, &msgs, EncodeMode::Strict).unwrap();
+        assert_eq!(MAX_FRAME_SIZE - (MAX_SSID_SIZE - 24), len);
+        assert_eq!([0x80, 0, 0, 0, 0xFF, 0xFF], buf[..6]);
+
+        let (parsed, elements) = parse_beacon_frame(&buf[..len]).unwrap().unwrap();
+        assert_eq!(beacon(), parsed);
+        let ids: std::vec::Vec<_> = elements.map(|e| e.unwrap().id).collect();
+        assert_eq!(
+            std::vec![
+                ELEMENT_SSID,
+                ELEMENT_SUPPORTED_RATES,
+                ELEMENT_VENDOR_SPECIFIC
+            ],
+            ids
+        );
+
+        let frame = decode_beacon_frame(&buf[..len]).unwrap().unwrap();
+        assert_eq!(beacon().source, frame.source);
+        assert_eq!(17, frame.counter);
+        assert_eq!(9, frame.pack.len());
+        for msg in frame.pack {
+            assert_eq!(Ok(self_id()), msg);
+        }
+    }
+
+    #[test]
+    fn other_frames() {
+        let mut buf = [0u8; MAX_FRAME_SIZE];
+        let len = encode_beacon_frame(&mut buf, &beacon(), 0, &[], EncodeMode::Strict).unwrap();
+
+        // probe response
+        let mut probe = buf;
+        probe[0] = 0x50;
+        assert!(decode_beacon_frame(&probe[..len]).unwrap().is_none());
+
+        // a beacon without the Remote ID element
+        let plain = len - (2 + 5 + 3);
+        assert!(parse_beacon_frame(&buf[..plain]).unwrap().is_some());
+        assert!(decode_beacon_frame(&buf[..plain]).unwrap().is_none());
+
+        // another vendor type of the same OUI
+        let mut other = buf;
+        other[plain + 5] = 0x0E;
+        assert!(decode_beacon_frame(&other[..len]).unwrap().is_none());
+
+        // truncated element
+        assert_eq!(
+            Ok(false),
+            decode_beacon_frame(&buf[..(len - 5)]).map(|f| f.is_some())
+        );
+
+        // a malformed element ahead of the Remote ID element
+        let mut malformed = buf;
+        malformed[plain - 2] = 0xFF;
+        assert!(parse_beacon_frame(&malformed[..len]).unwrap().is_some());
+        assert_eq!(
+            Ok(false),
+            decode_beacon_frame(&malformed[..len]).map(|f| f.is_some())
+        );
+
+        // trailing bytes after the Remote ID element
+        let mut fcs = [0u8; MAX_FRAME_SIZE + 4];
+        fcs[..len].copy_from_slice(&buf[..len]);
+        fcs[len..len + 4].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        assert!(decode_beacon_frame(&fcs[..len + 4]).unwrap().is_some());
+
+        // Remote ID element too short for its header
+        let mut short = buf;
+        short[plain + 1] = 4;
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: VENDOR_HEADER_SIZE,
+                actual: 4
+            }),
+            decode_beacon_frame(&short[..plain + 6]).map(|f| f.is_some())
+        );
+
+        let long_ssid = Beacon {
+            ssid: &[b'x'; 33],
+            ..beacon()
+        };
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "ssid length",
+                value: 33.
+            }),
+            encode_beacon_frame(&mut buf, &long_ssid, 0, &[], EncodeMode::Strict)
+        );
+    }
+}
@@ -0,0 +1,530 @@
+//! French "signalement électronique" broadcast, the Remote ID mandated in France by the
+//! arrêté of 27 December 2019 before ASTM F3411 was adopted.
+//!
+//! The data travels in a vendor specific element of Wi-Fi beacons as a list of TLVs, each a
+//! type, a length and a big endian value.
+
+use crate::codec::decode::DecodeError;
+use crate::codec::encode::{encode_latitude, encode_longitude, round, EncodeError, EncodeMode};
+use crate::data::basic_id::{BasicId, IdType, UAType};
+use crate::data::coordinate::Coordinate;
+use crate::data::location::{
+    HeightType, HorizontalAccuracy, Location, OperationalStatus, SpeedAccuracy, VerticalAccuracy,
+};
+use crate::data::system::{ClassificationType, OperatorLocationType, System, UaClassification};
+use crate::MAX_ID_BYTE_SIZE;
+
+use super::beacon::{parse_beacon_frame, ELEMENT_VENDOR_SPECIFIC};
+use super::{InformationElement, InformationElements};
+
+/// OUI of the French Secrétariat général de la défense et de la sécurité nationale
+pub const FRENCH_OUI: [u8; 3] = [0x6A, 0x5C, 0x35];
+
+/// Vendor specific type of the Remote ID element
+pub const VENDOR_TYPE_FRENCH: u8 = 0x01;
+
+/// Only version of the format
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Size of the French identifier: manufacturer trigram, model and serial number
+pub const FRENCH_ID_SIZE: usize = 30;
+
+pub const TLV_PROTOCOL_VERSION: u8 = 1;
+pub const TLV_FRENCH_ID: u8 = 2;
+pub const TLV_ANSI_CTA_ID: u8 = 3;
+pub const TLV_LATITUDE: u8 = 4;
+pub const TLV_LONGITUDE: u8 = 5;
+pub const TLV_ALTITUDE: u8 = 6;
+pub const TLV_HEIGHT: u8 = 7;
+pub const TLV_HOME_LATITUDE: u8 = 8;
+pub const TLV_HOME_LONGITUDE: u8 = 9;
+pub const TLV_GROUND_SPEED: u8 = 10;
+pub const TLV_HEADING: u8 = 11;
+
+/// Coordinates are sent in 1e-5 degrees, `Coordinate` uses 1e-7
+const COORDINATE_SCALE: i32 = 100;
+
+/// OUI and vendor type in front of the TLVs
+const VENDOR_HEADER_SIZE: usize = 3 + 1;
+
+/// Contents of a French broadcast, mapped onto the ASTM data types.
+///
+/// Only the fields the French format carries are filled in, everything else is "unknown".
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrenchRemoteId {
+    /// Manufacturer trigram, model and serial number padded with leading zeros
+    pub french_id: Option<[u8; FRENCH_ID_SIZE]>,
+    /// The ANSI/CTA-2063-A serial number as a Basic ID message
+    pub basic_id: Option<BasicId>,
+    /// Position, height above take-off, ground speed and heading.
+    ///
+    /// The altitude is sent relative to mean sea level and is kept in `geodetic_altitude`.
+    pub location: Location,
+    /// The take-off position as the operator location, `None` if it was not sent
+    pub system: Option<System>,
+}
+
+impl FrenchRemoteId {
+    /// A broadcast carrying only the French ID, all other fields unknown
+    pub fn new(french_id: [u8; FRENCH_ID_SIZE]) -> Self {
+        Self {
+            french_id: Some(french_id),
+            basic_id: None,
+            location: unknown_location(),
+            system: None,
+        }
+    }
+}
+
+fn unknown_location() -> Location {
+    Location {
+        operational_status: OperationalStatus::Undeclared,
+        height_type: HeightType::AboveTakeoff,
+        speed: None,
+        vertical_speed: None,
+        pressure_altitude: None,
+        geodetic_altitude: None,
+        track_direction: None,
+        horizontal_accuracy: HorizontalAccuracy::Unknown,
+        vertical_accuracy: VerticalAccuracy::Unknown,
+        latidute: None,
+        longitude: None,
+        height: None,
+        baro_altitude_accuracy: VerticalAccuracy::Unknown,
+        speed_accuracy: SpeedAccuracy::Unknown,
+        timestamp: None,
+        timestamp_accuracy: None,
+    }
+}
+
+fn home_system(latidute: Coordinate, longitude: Coordinate) -> System {
+    System {
+        classification_type: ClassificationType::Undeclared,
+        operator_location_type: OperatorLocationType::TakeOff,
+        operator_latidute: latidute,
+        operator_longitude: longitude,
+        area_count: 1,
+        area_radius: 0.,
+        area_ceiling: -1000.,
+        area_floor: -1000.,
+        ua_classification: UaClassification::undefined(),
+        operator_altitude: -1000.,
+        timestamp: None,
+    }
+}
+
+/// Check the length of a TLV value and return it as an array
+fn value<const N: usize>(tlv: &InformationElement) -> Result<[u8; N], DecodeError> {
+    tlv.body
+        .try_into()
+        .map_err(|_| DecodeError::FieldOutOfRange {
+            field: "tlv length",
+            value: tlv.body.len() as u32,
+        })
+}
+
+fn coordinate(tlv: &InformationElement) -> Result<Coordinate, DecodeError> {
+    let raw = i32::from_be_bytes(value(tlv)?);
+    Ok(Coordinate::from_raw(raw.saturating_mul(COORDINATE_SCALE)))
+}
+
+/// Decode a French vendor specific element, `None` for any other element
+pub fn decode_french_element(
+    element: &InformationElement,
+) -> Result<Option<FrenchRemoteId>, DecodeError> {
+    let body = element.body;
+    if element.id != ELEMENT_VENDOR_SPECIFIC
+        || body.len() < VENDOR_HEADER_SIZE
+        || body[..3] != FRENCH_OUI
+        || body[3] != VENDOR_TYPE_FRENCH
+    {
+        return Ok(None);
+    }
+
+    let mut remote_id = FrenchRemoteId {
+        french_id: None,
+        basic_id: None,
+        location: unknown_location(),
+        system: None,
+    };
+    let location = &mut remote_id.location;
+    let (mut home_latidute, mut home_longitude) = (None, None);
+
+    for tlv in InformationElements::new(&body[VENDOR_HEADER_SIZE..]) {
+        let tlv = tlv?;
+        match tlv.id {
+            TLV_PROTOCOL_VERSION => {
+                let [version] = value(&tlv)?;
+                if version != PROTOCOL_VERSION {
+                    return Err(DecodeError::UnsupportedProtocolVersion(version));
+                }
+            }
+            TLV_FRENCH_ID => remote_id.french_id = Some(value(&tlv)?),
+            TLV_ANSI_CTA_ID => {
+                remote_id.basic_id = Some(BasicId {
+                    id_type: IdType::SerialNumber,
+                    ua_type: UAType::None,
+                    uas_id: value::<MAX_ID_BYTE_SIZE>(&tlv)?,
+                })
+            }
+            TLV_LATITUDE => location.latidute = Some(coordinate(&tlv)?),
+            TLV_LONGITUDE => location.longitude = Some(coordinate(&tlv)?),
+            TLV_ALTITUDE => {
+                location.geodetic_altitude = Some(i16::from_be_bytes(value(&tlv)?) as f32)
+            }
+            TLV_HEIGHT => location.height = Some(i16::from_be_bytes(value(&tlv)?) as f32),
+            TLV_HOME_LATITUDE => home_latidute = Some(coordinate(&tlv)?),
+            TLV_HOME_LONGITUDE => home_longitude = Some(coordinate(&tlv)?),
+            TLV_GROUND_SPEED => {
+                let [speed] = value(&tlv)?;
+                location.speed = Some(speed as f32);
+            }
+            TLV_HEADING => {
+                let heading = u16::from_be_bytes(value(&tlv)?);
+                if heading >= 360 {
+                    return Err(DecodeError::FieldOutOfRange {
+                        field: "heading",
+                        value: heading as u32,
+                    });
+                }
+                location.track_direction = Some(heading);
+            }
+
+            // reserved for future use
+            _ => {}
+        }
+    }
+
+    if let (Some(latidute), Some(longitude)) = (home_latidute, home_longitude) {
+        remote_id.system = Some(home_system(latidute, longitude));
+    }
+
+    Ok(Some(remote_id))
+}
+
+/// Decode the French broadcast carried by an 802.11 frame, along with the transmitter address.
+///
+/// `frame` starts with the 802.11 header and ends after the last element, the caller strips
+/// the FCS. Returns `None` for frames that are not beacons or beacons without a French
+/// element. Elements are read up to the first malformed one, only errors of the French element
+/// itself are returned.
+pub fn decode_french_beacon(
+    frame: &[u8],
+) -> Result<Option<([u8; 6], FrenchRemoteId)>, DecodeError> {
+    let Some((beacon, elements)) = parse_beacon_frame(frame)? else {
+        return Ok(None);
+    };
+
+    for element in elements {
+        let Ok(element) = element else {
+            break;
+        };
+        if let Some(remote_id) = decode_french_element(&element)? {
+            return Ok(Some((beacon.source, remote_id)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Writes TLVs into a buffer, checking that each one fits
+struct TlvWriter<'a> {
+    buf: &'a mut [u8],
+    offset: usize,
+}
+
+impl TlvWriter<'_> {
+    fn put(&mut self, tlv_type: u8, value: &[u8]) -> Result<(), EncodeError> {
+        let expected = self.offset + 2 + value.len();
+        if self.buf.len() < expected {
+            return Err(EncodeError::BufferTooSmall {
+                expected,
+                actual: self.buf.len(),
+            });
+        }
+
+        self.buf[self.offset] = tlv_type;
+        self.buf[self.offset + 1] = value.len() as u8;
+        self.buf[(self.offset + 2)..expected].copy_from_slice(value);
+        self.offset = expected;
+
+        Ok(())
+    }
+
+    /// Write a coordinate already checked by [`encode_latitude`] or [`encode_longitude`]
+    fn put_coordinate(&mut self, tlv_type: u8, raw: i32) -> Result<(), EncodeError> {
+        // round half away from zero to 1e-5 degrees, widened as raw + half may overflow
+        let raw = raw as i64;
+        let half = (COORDINATE_SCALE / 2) as i64 * raw.signum();
+        let value = ((raw + half) / COORDINATE_SCALE as i64) as i32;
+        self.put(tlv_type, &value.to_be_bytes())
+    }
+
+    fn put_i16(
+        &mut self,
+        tlv_type: u8,
+        field: &'static str,
+        value: f32,
+        mode: EncodeMode,
+    ) -> Result<(), EncodeError> {
+        let value = mode.limit(field, Some(value), i16::MIN as f32, i16::MAX as f32)?;
+        match value {
+            Some(value) => self.put(tlv_type, &(round(value) as i16).to_be_bytes()),
+            // not a number, leave the field out
+            None => Ok(()),
+        }
+    }
+}
+
+/// Write the French vendor specific element, returning the number of bytes written.
+///
+/// Fields that are unknown are left out. Only the fields the format carries are encoded,
+/// coordinates are checked against ±90° and ±180° as in [`crate::codec::encode`].
+pub fn encode_french_element(
+    buf: &mut [u8],
+    remote_id: &FrenchRemoteId,
+    mode: EncodeMode,
+) -> Result<usize, EncodeError> {
+    let header_size = 2 + VENDOR_HEADER_SIZE;
+    if buf.len() < header_size {
+        return Err(EncodeError::BufferTooSmall {
+            expected: header_size,
+            actual: buf.len(),
+        });
+    }
+
+    buf[0] = ELEMENT_VENDOR_SPECIFIC;
+    buf[2..5].copy_from_slice(&FRENCH_OUI);
+    buf[5] = VENDOR_TYPE_FRENCH;
+
+    let mut writer = TlvWriter {
+        buf,
+        offset: header_size,
+    };
+    writer.put(TLV_PROTOCOL_VERSION, &[PROTOCOL_VERSION])?;
+
+    if let Some(french_id) = &remote_id.french_id {
+        writer.put(TLV_FRENCH_ID, french_id)?;
+    }
+    if let Some(basic_id) = &remote_id.basic_id {
+        writer.put(TLV_ANSI_CTA_ID, &basic_id.uas_id)?;
+    }
+
+    let location = &remote_id.location;
+    if let Some(latidute) = location.latidute {
+        writer.put_coordinate(TLV_LATITUDE, encode_latitude(Some(latidute), mode)?)?;
+    }
+    if let Some(longitude) = location.longitude {
+        writer.put_coordinate(TLV_LONGITUDE, encode_longitude(Some(longitude), mode)?)?;
+    }
+    if let Some(altitude) = location.geodetic_altitude {
+        writer.put_i16(TLV_ALTITUDE, "altitude", altitude, mode)?;
+    }
+    if let Some(height) = location.height {
+        writer.put_i16(TLV_HEIGHT, "height", height, mode)?;
+    }
+
+    if let Some(system) = &remote_id.system {
+        let home_latitude = encode_latitude(Some(system.operator_latidute), mode)?;
+        writer.put_coordinate(TLV_HOME_LATITUDE, home_latitude)?;
+        let home_longitude = encode_longitude(Some(system.operator_longitude), mode)?;
+        writer.put_coordinate(TLV_HOME_LONGITUDE, home_longitude)?;
+    }
+
+    if let Some(speed) = mode.limit("speed", location.speed, 0., u8::MAX as f32)? {
+        writer.put(TLV_GROUND_SPEED, &[round(speed) as u8])?;
+    }
+    if let Some(heading) = location.track_direction {
+        let heading = mode.limit("heading", Some(heading as f32), 0., 360.)?;
+        if let Some(heading) = heading {
+            writer.put(TLV_HEADING, &(heading as u16 % 360).to_be_bytes())?;
+        }
+    }
+
+    let len = writer.offset;
+    buf[1] = (len - 2) as u8;
+
+    Ok(len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transport::wifi::beacon::{encode_beacon_frame, Beacon};
+
+    const FRENCH_ID: &[u8; FRENCH_ID_SIZE] = b"ILLMOD000000000000000000012345";
+
+    fn remote_id() -> FrenchRemoteId {
+        let mut remote_id = FrenchRemoteId::new(*FRENCH_ID);
+        remote_id.basic_id = Some(BasicId {
+            id_type: IdType::SerialNumber,
+            ua_type: UAType::None,
+            uas_id: *b"1596F359746167260749",
+        });
+
+        let location = &mut remote_id.location;
+        location.latidute = Some(Coordinate::from_raw(488583700));
+        location.longitude = Some(Coordinate::from_raw(-22944800));
+        location.geodetic_altitude = Some(120.);
+        location.height = Some(-3.);
+        location.speed = Some(12.);
+        location.track_direction = Some(270);
+
+        remote_id.system = Some(home_system(
+            Coordinate::from_raw(488580000),
+            Coordinate::from_raw(-22950000),
+        ));
+
+        remote_id
+    }
+
+    #[test]
+    fn encode_element() {
+        let mut buf = [0u8; 255];
+        let len = encode_french_element(&mut buf, &remote_id(), EncodeMode::Strict).unwrap();
+        assert_eq!(6 + 3 + 32 + 22 + 6 + 6 + 4 + 4 + 6 + 6 + 3 + 4, len);
+
+        assert_eq!(
+            [0xDD, len as u8 - 2, 0x6A, 0x5C, 0x35, 0x01, 1, 1, 1],
+            buf[..9]
+        );
+        assert_eq!([2, 30], buf[9..11]);
+        assert_eq!(FRENCH_ID, &buf[11..41]);
+        assert_eq!([3, 20], buf[41..43]);
+
+        let rest = [
+            4, 4, 0x00, 0x4A, 0x8D, 0x4D, // 48.85837
+            5, 4, 0xFF, 0xFC, 0x7F, 0xB8, // -2.29448
+            6, 2, 0x00, 0x78, // 120 m
+            7, 2, 0xFF, 0xFD, // -3 m
+            8, 4, 0x00, 0x4A, 0x8D, 0x28, // 48.858
+            9, 4, 0xFF, 0xFC, 0x7F, 0x84, // -2.295
+            10, 1, 12, // 12 m/s
+            11, 2, 0x01, 0x0E, // 270°
+        ];
+        assert_eq!(rest, buf[63..len]);
+    }
+
+    #[test]
+    fn beacon_round_trip() {
+        let mut element = [0u8; 255];
+        let element_len =
+            encode_french_element(&mut element, &remote_id(), EncodeMode::Strict).unwrap();
+
+        // an ASTM beacon, with its Remote ID element replaced by the French one
+        let beacon = Beacon {
+            source: [0x60, 0x60, 0x1F, 0x01, 0x02, 0x03],
+            bssid: [0x60, 0x60, 0x1F, 0x01, 0x02, 0x03],
+            timestamp: 0,
+            beacon_interval: 100,
+            capability: 0x0421,
+            ssid: b"ILLMOD000000000000000000012345",
+        };
+        let mut frame = [0u8; 512];
+        let astm_len =
+            encode_beacon_frame(&mut frame, &beacon, 0, &[], EncodeMode::Strict).unwrap();
+        let start = astm_len - 10;
+        frame[start..(start + element_len)].copy_from_slice(&element[..element_len]);
+        let len = start + element_len;
+
+        let (source, decoded) = decode_french_beacon(&frame[..len]).unwrap().unwrap();
+        assert_eq!(beacon.source, source);
+        assert_eq!(remote_id(), decoded);
+
+        // trailing bytes after the French element
+        frame[len..(len + 4)].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        assert!(decode_french_beacon(&frame[..(len + 4)]).unwrap().is_some());
+
+        // a malformed element ahead of the French element
+        let mut malformed = frame;
+        malformed[start - 2] = 0xFF;
+        assert_eq!(Ok(None), decode_french_beacon(&malformed[..len]));
+
+        // the ASTM element is not mistaken for a French one
+        let mut astm = [0u8; 512];
+        encode_beacon_frame(&mut astm, &beacon, 0, &[], EncodeMode::Strict).unwrap();
+        assert_eq!(Ok(None), decode_french_beacon(&astm[..astm_len]));
+    }
+
+    #[test]
+    fn decode_errors() {
+        let element = |body: &[u8]| {
+            let mut data = [0u8; 64];
+            data[..4].copy_from_slice(&[0x6A, 0x5C, 0x35, 0x01]);
+            data[4..(4 + body.len())].copy_from_slice(body);
+            decode_french_element(&InformationElement {
+                id: ELEMENT_VENDOR_SPECIFIC,
+                body: &data[..(4 + body.len())],
+            })
+            .map(|r| r.map(|r| r.location))
+        };
+
+        assert!(element(&[1, 1, 1]).unwrap().is_some());
+        assert_eq!(
+            Err(DecodeError::UnsupportedProtocolVersion(2)),
+            element(&[1, 1, 2])
+        );
+        assert_eq!(
+            Err(DecodeError::FieldOutOfRange {
+                field: "tlv length",
+                value: 3
+            }),
+            element(&[4, 3, 0, 0, 0])
+        );
+        assert_eq!(
+            Err(DecodeError::FieldOutOfRange {
+                field: "heading",
+                value: 360
+            }),
+            element(&[11, 2, 0x01, 0x68])
+        );
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 4,
+                actual: 2
+            }),
+            element(&[5, 4, 0, 0])
+        );
+
+        // unknown TLV types are skipped
+        let location = element(&[12, 1, 0, 10, 1, 7]).unwrap().unwrap();
+        assert_eq!(Some(7.), location.speed);
+    }
+
+    #[test]
+    fn encode_out_of_range() {
+        let mut remote_id = remote_id();
+        remote_id.location.speed = Some(300.);
+
+        let mut buf = [0u8; 255];
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "speed",
+                value: 300.
+            }),
+            encode_french_element(&mut buf, &remote_id, EncodeMode::Strict)
+        );
+
+        let len = encode_french_element(&mut buf, &remote_id, EncodeMode::Clamp).unwrap();
+        assert_eq!([10, 1, 255], buf[(len - 7)..(len - 4)]);
+
+        let mut remote_id = self::remote_id();
+        remote_id.location.longitude = Some(Coordinate::from_raw(i32::MAX));
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "longitude",
+                value: Coordinate::from_raw(i32::MAX).degrees()
+            }),
+            encode_french_element(&mut buf, &remote_id, EncodeMode::Strict)
+        );
+        remote_id.location.latidute = Some(Coordinate::from_degrees(-90.5));
+        assert_eq!(
+            Err(EncodeError::FieldOutOfRange {
+                field: "latitude",
+                value: Coordinate::from_degrees(-90.5).degrees()
+            }),
+            encode_french_element(&mut buf, &remote_id, EncodeMode::Strict)
+        );
+        encode_french_element(&mut buf, &remote_id, EncodeMode::Clamp).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+use crate::codec::decode::{DecodeError, MessagePack};
+use crate::codec::encode::EncodeError;
+
+pub mod beacon;
+pub mod french;
+pub mod nan;
+
+/// Size of an 802.11 management frame header
+pub const MANAGEMENT_HEADER_SIZE: usize = 24;
+
+/// Wi-Fi Alliance OUI
+pub const WIFI_ALLIANCE_OUI: [u8; 3] = [0x50, 0x6F, 0x9A];
+
+/// Management frame subtype of Beacon frames
+const SUBTYPE_BEACON: u8 = 0x8;
+
+/// Management frame subtype of Action frames
+const SUBTYPE_ACTION: u8 = 0xD;
+
+/// A Remote ID broadcast received over Wi-Fi
+#[derive(Debug, Clone)]
+pub struct RemoteIdFrame<'a> {
+    /// MAC address of the transmitter
+    pub source: [u8; 6],
+    pub counter: u8,
+    pub pack: MessagePack<'a>,
+}
+
+/// Addresses of an 802.11 management frame header
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct ManagementHeader {
+    subtype: u8,
+    destination: [u8; 6],
+    source: [u8; 6],
+    bssid: [u8; 6],
+}
+
+/// Write a management frame header, returning the number of bytes written
+fn encode_management_header(
+    buf: &mut [u8],
+    header: &ManagementHeader,
+) -> Result<usize, EncodeError> {
+    if buf.len() < MANAGEMENT_HEADER_SIZE {
+        return Err(EncodeError::BufferTooSmall {
+            expected: MANAGEMENT_HEADER_SIZE,
+            actual: buf.len(),
+        });
+    }
+
+    // Frame Control: protocol version 0, type 0 (management), subtype, no flags
+    buf[0] = header.subtype << 4;
+    buf[1] = 0;
+    // Duration
+    buf[2..4].fill(0);
+    buf[4..10].copy_from_slice(&header.destination);
+    buf[10..16].copy_from_slice(&header.source);
+    buf[16..22].copy_from_slice(&header.bssid);
+    // Sequence Control, filled in by the driver
+    buf[22..24].fill(0);
+
+    Ok(MANAGEMENT_HEADER_SIZE)
+}
+
+/// Read a management frame header, `None` for other frame types
+fn decode_management_header(frame: &[u8]) -> Result<Option<ManagementHeader>, DecodeError> {
+    if frame.len() < MANAGEMENT_HEADER_SIZE {
+        return Err(DecodeError::BufferTooShort {
+            expected: MANAGEMENT_HEADER_SIZE,
+            actual: frame.len(),
+        });
+    }
+
+    // protocol version 0 and type 0 (management)
+    if frame[0] & 0x0F != 0 {
+        return Ok(None);
+    }
+
+    let address = |offset: usize| -> [u8; 6] { frame[offset..(offset + 6)].try_into().unwrap() };
+
+    Ok(Some(ManagementHeader {
+        subtype: frame[0] >> 4,
+        destination: address(4),
+        source: address(10),
+        bssid: address(16),
+    }))
+}
+
+/// A single information element of a management frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InformationElement<'a> {
+    pub id: u8,
+    pub body: &'a [u8],
+}
+
+/// Iterator over information elements, each an ID, a length and the body.
+///
+/// An element running past the end of the data yields an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct InformationElements<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> InformationElements<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for InformationElements<'a> {
+    type Item = Result<InformationElement<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let data = core::mem::take(&mut self.data);
+        if data.len() < 2 {
+            return Some(Err(DecodeError::BufferTooShort {
+                expected: 2,
+                actual: data.len(),
+            }));
+        }
+
+        let length = data[1] as usize;
+        let rest = &data[2..];
+        if rest.len() < length {
+            return Some(Err(DecodeError::BufferTooShort {
+                expected: length,
+                actual: rest.len(),
+            }));
+        }
+
+        let (body, rest) = rest.split_at(length);
+        self.data = rest;
+
+        Some(Ok(InformationElement { id: data[0], body }))
+    }
+}
@@ -0,0 +1,327 @@
+use crate::codec::decode::{decode_message_pack, DecodeError};
+use crate::codec::encode::{encode_message_pack, EncodeError, EncodeMode};
+use crate::codec::MAX_MESSAGE_PACK_SIZE;
+use crate::data::RemoteIDMessage;
+
+use super::{
+    decode_management_header, encode_management_header, ManagementHeader, RemoteIdFrame,
+    MANAGEMENT_HEADER_SIZE, SUBTYPE_ACTION, WIFI_ALLIANCE_OUI,
+};
+
+/// First 6 bytes of the SHA-256 hash of "org.opendroneid.remoteid"
+pub const REMOTE_ID_SERVICE_ID: [u8; 6] = [0x88, 0x69, 0x19, 0x9D, 0x92, 0x09];
+
+/// NAN Network ID, destination and BSSID of Service Discovery Frames
+pub const NAN_NETWORK_ID: [u8; 6] = [0x51, 0x6F, 0x9A, 0x01, 0x00, 0x00];
+
+/// Public Action frame category
+const CATEGORY_PUBLIC_ACTION: u8 = 0x04;
+
+/// Vendor Specific public action
+const ACTION_VENDOR_SPECIFIC: u8 = 0x09;
+
+/// Wi-Fi Alliance OUI type of NAN
+const OUI_TYPE_NAN: u8 = 0x13;
+
+/// Category, action, OUI and OUI type in front of the attributes
+const ACTION_HEADER_SIZE: usize = 6;
+
+pub const ATTRIBUTE_SERVICE_DESCRIPTOR: u8 = 0x03;
+pub const ATTRIBUTE_SERVICE_DESCRIPTOR_EXTENSION: u8 = 0x0E;
+
+/// Service ID, instance ID, requestor instance ID, service control and service info length
+const SERVICE_DESCRIPTOR_HEADER_SIZE: usize = 6 + 1 + 1 + 1 + 1;
+
+/// Publish, with service info present
+const SERVICE_CONTROL_PUBLISH: u8 = 0x10;
+
+/// Instance ID of the Remote ID publish function
+const INSTANCE_ID: u8 = 0x01;
+
+/// Size of a Service Discovery Frame carrying a full Message Pack
+pub const MAX_FRAME_SIZE: usize = frame_size(MAX_MESSAGE_PACK_SIZE);
+
+/// Size of a Service Discovery Frame carrying a Message Pack of `pack_len` bytes
+const fn frame_size(pack_len: usize) -> usize {
+    // the Service Descriptor Attribute holds the counter and pack, the extension attribute
+    // takes 7 bytes
+    MANAGEMENT_HEADER_SIZE
+        + ACTION_HEADER_SIZE
+        + 3
+        + SERVICE_DESCRIPTOR_HEADER_SIZE
+        + 1
+        + pack_len
+        + 7
+}
+
+/// Encode `msgs` as a Message Pack in a NAN Service Discovery Frame sent from `source`,
+/// returning the number of bytes written.
+///
+/// The frame starts with the 802.11 header and ends after the last attribute, the FCS is
+/// left to the hardware.
+pub fn encode_nan_frame(
+    buf: &mut [u8],
+    source: [u8; 6],
+    counter: u8,
+    msgs: &[RemoteIDMessage],
+    mode: EncodeMode,
+) -> Result<usize, EncodeError> {
+    let mut pack = [0u8; MAX_MESSAGE_PACK_SIZE];
+    let pack_len = encode_message_pack(&mut pack, msgs, mode)?;
+
+    let service_info_len = 1 + pack_len;
+    let descriptor_len = SERVICE_DESCRIPTOR_HEADER_SIZE + service_info_len;
+    let expected = frame_size(pack_len);
+    if buf.len() < expected {
+        return Err(EncodeError::BufferTooSmall {
+            expected,
+            actual: buf.len(),
+        });
+    }
+
+    let header = ManagementHeader {
+        subtype: SUBTYPE_ACTION,
+        destination: NAN_NETWORK_ID,
+        source,
+        bssid: NAN_NETWORK_ID,
+    };
+    let mut offset = encode_management_header(buf, &header)?;
+
+    buf[offset] = CATEGORY_PUBLIC_ACTION;
+    buf[offset + 1] = ACTION_VENDOR_SPECIFIC;
+    buf[(offset + 2)..(offset + 5)].copy_from_slice(&WIFI_ALLIANCE_OUI);
+    buf[offset + 5] = OUI_TYPE_NAN;
+    offset += ACTION_HEADER_SIZE;
+
+    // Service Descriptor Attribute
+    buf[offset] = ATTRIBUTE_SERVICE_DESCRIPTOR;
+    buf[(offset + 1)..(offset + 3)].copy_from_slice(&(descriptor_len as u16).to_le_bytes());
+    offset += 3;
+    buf[offset..(offset + 6)].copy_from_slice(&REMOTE_ID_SERVICE_ID);
+    buf[offset + 6] = INSTANCE_ID;
+    // Requestor Instance ID
+    buf[offset + 7] = 0;
+    buf[offset + 8] = SERVICE_CONTROL_PUBLISH;
+    buf[offset + 9] = service_info_len as u8;
+    offset += SERVICE_DESCRIPTOR_HEADER_SIZE;
+
+    // Service Info: message counter and Message Pack
+    buf[offset] = counter;
+    buf[(offset + 1)..(offset + service_info_len)].copy_from_slice(&pack[..pack_len]);
+    offset += service_info_len;
+
+    // Service Descriptor Extension Attribute
+    buf[offset] = ATTRIBUTE_SERVICE_DESCRIPTOR_EXTENSION;
+    buf[(offset + 1)..(offset + 3)].copy_from_slice(&4u16.to_le_bytes());
+    buf[offset + 3] = INSTANCE_ID;
+    // Control: Service Update Indicator present
+    buf[(offset + 4)..(offset + 6)].copy_from_slice(&0x0200u16.to_le_bytes());
+    // Service Update Indicator
+    buf[offset + 6] = counter;
+    offset += 7;
+
+    Ok(offset)
+}
+
+/// Decode the Remote ID broadcast carried by an 802.11 frame.
+///
+/// `frame` starts with the 802.11 header, e.g. a monitor mode capture with the radiotap
+/// header removed. Returns `None` for frames that are not Remote ID Service Discovery Frames.
+pub fn decode_nan_frame(frame: &[u8]) -> Result<Option<RemoteIdFrame<'_>>, DecodeError> {
+    let Some(header) = decode_management_header(frame)? else {
+        return Ok(None);
+    };
+
+    let body = &frame[MANAGEMENT_HEADER_SIZE..];
+    if header.subtype != SUBTYPE_ACTION
+        || body.len() < ACTION_HEADER_SIZE
+        || body[0] != CATEGORY_PUBLIC_ACTION
+        || body[1] != ACTION_VENDOR_SPECIFIC
+        || body[2..5] != WIFI_ALLIANCE_OUI
+        || body[5] != OUI_TYPE_NAN
+    {
+        return Ok(None);
+    }
+
+    for attribute in NanAttributes::new(&body[ACTION_HEADER_SIZE..]) {
+        let attribute = attribute?;
+        let body = attribute.body;
+        if attribute.id != ATTRIBUTE_SERVICE_DESCRIPTOR
+            || body.len() < SERVICE_DESCRIPTOR_HEADER_SIZE
+            || body[..6] != REMOTE_ID_SERVICE_ID
+        {
+            continue;
+        }
+
+        let service_info_len = body[9] as usize;
+        let service_info = &body[SERVICE_DESCRIPTOR_HEADER_SIZE..];
+        if service_info_len == 0 || service_info.len() < service_info_len {
+            return Err(DecodeError::BufferTooShort {
+                expected: service_info_len.max(1),
+                actual: service_info.len(),
+            });
+        }
+
+        let pack = decode_message_pack(&service_info[1..service_info_len])?;
+        return Ok(Some(RemoteIdFrame {
+            source: header.source,
+            counter: service_info[0],
+            pack,
+        }));
+    }
+
+    Ok(None)
+}
+
+/// A single NAN attribute
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NanAttribute<'a> {
+    pub id: u8,
+    pub body: &'a [u8],
+}
+
+/// Iterator over the attributes of a NAN frame, each an ID, a 16 bit length and the body.
+///
+/// An attribute running past the end of the data yields an error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct NanAttributes<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> NanAttributes<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for NanAttributes<'a> {
+    type Item = Result<NanAttribute<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let data = core::mem::take(&mut self.data);
+        if data.len() < 3 {
+            return Some(Err(DecodeError::BufferTooShort {
+                expected: 3,
+                actual: data.len(),
+            }));
+        }
+
+        let length = u16::from_le_bytes([data[1], data[2]]) as usize;
+        let rest = &data[3..];
+        if rest.len() < length {
+            return Some(Err(DecodeError::BufferTooShort {
+                expected: length,
+                actual: rest.len(),
+            }));
+        }
+
+        let (body, rest) = rest.split_at(length);
+        self.data = rest;
+
+        Some(Ok(NanAttribute { id: data[0], body }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::codec::MAX_MESSAGES_IN_PACK;
+    use crate::data::fixtures::operator_id;
+
+    const SOURCE: [u8; 6] = [0x02, 0x11, 0x22, 0x33, 0x44, 0x55];
+
+    #[test]
+    fn encode_frame() {
+        let mut buf = [0u8; MAX_FRAME_SIZE];
+        let len =
+            encode_nan_frame(&mut buf, SOURCE, 5, &[operator_id()], EncodeMode::Strict).unwrap();
+        assert_eq!(24 + 6 + 3 + 10 + 1 + 28 + 7, len);
+
+        let expected_header = [
+            0xD0, 0x00, 0x00, 0x00, 0x51, 0x6F, 0x9A, 0x01, 0x00, 0x00, 0x02, 0x11, 0x22, 0x33,
+            0x44, 0x55, 0x51, 0x6F, 0x9A, 0x01, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(expected_header, buf[..24]);
+
+        let expected_attribute = [
+            0x04, 0x09, 0x50, 0x6F, 0x9A, 0x13, 0x03, 39, 0, 0x88, 0x69, 0x19, 0x9D, 0x92, 0x09,
+            0x01, 0x00, 0x10, 29, 5, 0xF2, 25, 1, 0x52, 0x00,
+        ];
+        assert_eq!(expected_attribute, buf[24..49]);
+        assert_eq!([0x0E, 4, 0, 0x01, 0x00, 0x02, 5], buf[(len - 7)..len]);
+    }
+
+    #[test]
+    fn frame_round_trip() {
+        let msgs: [RemoteIDMessage; MAX_MESSAGES_IN_PACK] = core::array::from_fn(|_| operator_id());
+
+        let mut buf = [0u8; MAX_FRAME_SIZE + 4];
+        let len = encode_nan_frame(&mut buf, SOURCE, 200, &msgs, EncodeMode::Strict).unwrap();
+        assert_eq!(MAX_FRAME_SIZE, len);
+
+        // a trailing FCS is ignored
+        let frame = decode_nan_frame(&buf[..(len + 4)]).unwrap().unwrap();
+        assert_eq!(SOURCE, frame.source);
+        assert_eq!(200, frame.counter);
+        assert_eq!(9, frame.pack.len());
+        for msg in frame.pack {
+            assert_eq!(Ok(operator_id()), msg);
+        }
+
+        let attributes: std::vec::Vec<_> = NanAttributes::new(&buf[30..len])
+            .map(|a| a.unwrap().id)
+            .collect();
+        assert_eq!(
+            std::vec![
+                ATTRIBUTE_SERVICE_DESCRIPTOR,
+                ATTRIBUTE_SERVICE_DESCRIPTOR_EXTENSION
+            ],
+            attributes
+        );
+    }
+
+    #[test]
+    fn other_frames() {
+        let mut buf = [0u8; MAX_FRAME_SIZE];
+        let len = encode_nan_frame(&mut buf, SOURCE, 0, &[], EncodeMode::Strict).unwrap();
+        assert!(decode_nan_frame(&buf[..len]).unwrap().is_some());
+
+        // beacon
+        let mut beacon = buf;
+        beacon[0] = 0x80;
+        assert!(decode_nan_frame(&beacon[..len]).unwrap().is_none());
+
+        // another NAN service
+        let mut other = buf;
+        other[33] ^= 0xFF;
+        assert!(decode_nan_frame(&other[..len]).unwrap().is_none());
+
+        // data frame
+        let mut data = buf;
+        data[0] = 0x08;
+        assert!(decode_nan_frame(&data[..len]).unwrap().is_none());
+
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 24,
+                actual: 10
+            }),
+            decode_nan_frame(&buf[..10]).map(|f| f.is_some())
+        );
+
+        // truncated in the middle of the service descriptor
+        assert_eq!(
+            Err(DecodeError::BufferTooShort {
+                expected: 14,
+                actual: 5
+            }),
+            decode_nan_frame(&buf[..38]).map(|f| f.is_some())
+        );
+    }
+}
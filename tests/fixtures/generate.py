@@ -0,0 +1,359 @@
+#!/usr/bin/env python3
+"""Generate the Remote ID capture fixtures used by the pcap reader tests, and the DRIP
+known-answer vectors.
+
+remote_id.pcapng holds a Bluetooth LE interface (LINKTYPE_BLUETOOTH_LE_LL_WITH_PHDR) and an
+802.11 interface (LINKTYPE_IEEE802_11_RADIOTAP) with, in order:
+
+  1. legacy ADV_NONCONN_IND with a Basic ID message, counter 7, RSSI -60
+  2. advertisement without Remote ID
+  3. advertisement without Remote ID and a truncated local name
+  4. ADV_EXT_IND pointing to the auxiliary packet
+  5. AUX_ADV_IND on the coded PHY with a Basic ID + Self ID pack, counter 8, RSSI -71
+  6. beacon with FCS and an Operator ID pack, counter 9, RSSI -55
+  7. ACK
+  8. NAN Service Discovery Frame with a Basic ID pack, counter 10, RSSI -48
+
+remote_id.pcap is a big-endian nanosecond pcap capture holding packet 1 only.
+
+remote_id.btsnoop is a btmon (Linux monitor) capture with, in order:
+
+  1. New Index and LE Set Scan Enable
+  2. LE Advertising Report with an advertisement without Remote ID, the truncated
+     advertisement of packet 3 above and the Basic ID of packet 1 above
+  3. Command Complete
+  4. LE Extended Advertising Report, incomplete
+  5. LE Extended Advertising Report with the Basic ID + Self ID pack of packet 4 above
+  6. ACL data
+
+hhit_ed25519.bin is the HHIT with RAA 10 and HDA 20 of the public key of RFC 8032 test 1,
+hashed with the cSHAKE128 below. It is checked against the NIST SP 800-185 samples.
+"""
+
+import hashlib
+import struct
+import zlib
+from pathlib import Path
+
+BASE_TIME = 1714816800  # 2024-05-04 10:00:00 UTC
+
+BLE_ADDRESS = bytes.fromhex("c0ffee123456")  # as written, sent reversed
+WIFI_ADDRESS = bytes.fromhex("0242ac110002")
+
+
+def basic_id():
+    return bytes([0x02, 0x10]) + b"1596F359746167260749" + bytes(3)
+
+
+def self_id():
+    return bytes([0x32, 0x00]) + b"Bridge survey".ljust(23, b"\0")
+
+
+def operator_id():
+    return bytes([0x52, 0x00]) + b"FIN87astrdge12k8".ljust(20, b"\0") + bytes(3)
+
+
+def pack(*messages):
+    return bytes([0xF2, 25, len(messages)]) + b"".join(messages)
+
+
+def service_data(counter, payload):
+    data = bytes([0x16, 0xFA, 0xFF, 0x0D, counter]) + payload
+    return bytes([len(data)]) + data
+
+
+def ble_crc(pdu, init=0x555555):
+    def swap(b):
+        return int(f"{b:08b}"[::-1], 2)
+
+    state = swap(init & 0xFF) | swap((init >> 8) & 0xFF) << 8 | swap(init >> 16) << 16
+    for byte in pdu:
+        for _ in range(8):
+            bit = (state ^ byte) & 1
+            byte >>= 1
+            state >>= 1
+            if bit:
+                state |= 1 << 23
+                state ^= 0x5A6000
+    return struct.pack("<I", state)[:3]
+
+
+def ble_packet(channel, rssi, pdu_type, payload, coded=False):
+    flags = 0x0001 | 0x0002 | 0x0400 | 0x0800 | (0x8000 if coded else 0)
+    header = struct.pack("<BbbBIH", channel, rssi, -95, 0, 0x8E89BED6, flags)
+    pdu = bytes([pdu_type, len(payload)]) + payload
+    ci = b"\x00" if coded else b""
+    return header + struct.pack("<I", 0x8E89BED6) + ci + pdu + ble_crc(pdu)
+
+
+def legacy_adv():
+    data = service_data(7, basic_id())
+    # random address
+    return ble_packet(37, -60, 0x42, BLE_ADDRESS[::-1] + data)
+
+
+def unrelated_adv():
+    return ble_packet(38, -80, 0x40, BLE_ADDRESS[::-1] + bytes([2, 0x01, 0x06]))
+
+
+def truncated_ad():
+    # the local name claims 20 bytes, only 3 follow
+    return bytes([2, 0x01, 0x06, 20, 0x09]) + b"odi"
+
+
+def truncated_adv():
+    return ble_packet(39, -75, 0x42, BLE_ADDRESS[::-1] + truncated_ad())
+
+
+def ext_adv_ind():
+    # ADI and AuxPtr, no advertiser address
+    header = bytes([0x18, 0x01, 0x10, 0x05, 0x2C, 0x01])
+    return ble_packet(39, -70, 0x07, bytes([len(header)]) + header, coded=True)
+
+
+def aux_adv_ind():
+    # AdvA and ADI
+    header = bytes([0x09]) + BLE_ADDRESS[::-1] + bytes([0x01, 0x10])
+    data = service_data(8, pack(basic_id(), self_id()))
+    return ble_packet(5, -71, 0x07, bytes([len(header)]) + header + data, coded=True)
+
+
+def management_header(subtype, destination, bssid):
+    return bytes([subtype << 4, 0, 0, 0]) + destination + WIFI_ADDRESS + bssid + bytes(2)
+
+
+def beacon():
+    ssid = b"RID-1596F359"
+    body = struct.pack("<QHH", 0, 100, 0x0421)
+    body += bytes([0, len(ssid)]) + ssid
+    body += bytes([1, 1, 0x8C])
+    vendor = bytes([0xFA, 0x0B, 0xBC, 0x0D, 9]) + pack(operator_id())
+    body += bytes([0xDD, len(vendor)]) + vendor
+    frame = management_header(0x8, b"\xff" * 6, WIFI_ADDRESS) + body
+
+    # Flags (FCS), Rate, Channel, antenna signal
+    radiotap = struct.pack("<BBHI", 0, 0, 15, 0x2E)
+    radiotap += struct.pack("<BBHHb", 0x10, 12, 2437, 0x00A0, -55)
+    return radiotap + frame + struct.pack("<I", zlib.crc32(frame))
+
+
+def ack():
+    radiotap = struct.pack("<BBHI", 0, 0, 8, 0)
+    return radiotap + bytes([0xD4, 0, 0, 0]) + WIFI_ADDRESS
+
+
+def nan():
+    nan_id = bytes.fromhex("516f9a010000")
+    body = bytes([4, 9, 0x50, 0x6F, 0x9A, 0x13])
+    info = bytes([10]) + pack(basic_id())
+    sda = bytes.fromhex("88 69 19 9d 92 09") + bytes([1, 0, 0x10, len(info)]) + info
+    body += bytes([0x03]) + struct.pack("<H", len(sda)) + sda
+    body += bytes([0x0E, 4, 0, 1, 0x00, 0x02, 10])
+    frame = management_header(0xD, nan_id, nan_id) + body
+
+    # TSFT and antenna signal in the first word, an empty extended present word
+    radiotap = struct.pack("<BBHII", 0, 0, 25, 0x80000021, 0)
+    # TSFT is aligned to 8 bytes
+    radiotap += struct.pack("<4xQb", 123456, -48)
+    return radiotap + frame
+
+
+def block(block_type, body):
+    body += bytes(-len(body) % 4)
+    length = len(body) + 12
+    return struct.pack("<II", block_type, length) + body + struct.pack("<I", length)
+
+
+def option(code, value):
+    return struct.pack("<HH", code, len(value)) + value + bytes(-len(value) % 4)
+
+
+def enhanced_packet(interface, timestamp, data):
+    high, low = timestamp >> 32, timestamp & 0xFFFFFFFF
+    return block(6, struct.pack("<IIIII", interface, high, low, len(data), len(data)) + data)
+
+
+def pcapng():
+    out = block(0x0A0D0D0A, struct.pack("<IHHq", 0x1A2B3C4D, 1, 0, -1))
+    # nanosecond timestamps on the Bluetooth interface, microseconds on Wi-Fi
+    out += block(1, struct.pack("<HHI", 256, 0, 0) + option(9, b"\x09") + option(0, b""))
+    out += block(1, struct.pack("<HHI", 127, 0, 0))
+    # name resolution block, skipped by the reader
+    out += block(4, struct.pack("<HH", 0, 0))
+
+    ns = BASE_TIME * 10**9
+    us = BASE_TIME * 10**6
+    out += enhanced_packet(0, ns + 125_000_000, legacy_adv())
+    out += enhanced_packet(0, ns + 250_000_000, unrelated_adv())
+    out += enhanced_packet(0, ns + 300_000_000, truncated_adv())
+    out += enhanced_packet(0, ns + 375_000_000, ext_adv_ind())
+    out += enhanced_packet(0, ns + 375_400_000, aux_adv_ind())
+    out += enhanced_packet(1, us + 500_000, beacon())
+    out += enhanced_packet(1, us + 500_100, ack())
+    out += enhanced_packet(1, us + 750_000, nan())
+    return out
+
+
+def pcap():
+    out = struct.pack(">IHHiIII", 0xA1B23C4D, 2, 4, 0, 0, 0xFFFF, 256)
+    data = legacy_adv()
+    out += struct.pack(">IIII", BASE_TIME, 125_000_000, len(data), len(data)) + data
+    return out
+
+
+BTSNOOP_EPOCH = 0x00DCDDB30F2F8000
+
+
+def btsnoop_record(opcode, micros, data):
+    timestamp = (BASE_TIME * 10**6 + micros) + BTSNOOP_EPOCH
+    return struct.pack(">IIIIq", len(data), len(data), opcode, 0, timestamp) + data
+
+
+def le_meta(subevent, parameters):
+    parameters = bytes([subevent]) + parameters
+    return bytes([0x3E, len(parameters)]) + parameters
+
+
+def advertising_report(event_type, address, data, rssi):
+    header = struct.pack("<BB", event_type, 1) + address[::-1]
+    return header + bytes([len(data)]) + data + struct.pack("b", rssi)
+
+
+def extended_advertising_report(event_type, address, data, rssi):
+    header = struct.pack("<HB", event_type, 1) + address[::-1]
+    header += struct.pack("<BBBbbHB", 1, 3, 0, 127, rssi, 0, 0) + bytes(6)
+    return header + bytes([len(data)]) + data
+
+
+def btsnoop():
+    out = b"btsnoop\0" + struct.pack(">II", 1, 2001)
+    out += btsnoop_record(0, 0, bytes([0, 1]) + bytes(6) + b"hci0".ljust(8, b"\0"))
+    out += btsnoop_record(2, 10, bytes([0x0C, 0x20, 2, 1, 0]))
+
+    reports = advertising_report(0, bytes.fromhex("0a0b0c0d0e0f"), bytes([2, 0x01, 0x06]), -90)
+    reports += advertising_report(3, bytes.fromhex("0a0b0c0d0e0f"), truncated_ad(), -75)
+    reports += advertising_report(3, BLE_ADDRESS, service_data(7, basic_id()), -60)
+    out += btsnoop_record(3, 125_000, le_meta(0x02, bytes([3]) + reports))
+    out += btsnoop_record(3, 125_100, bytes([0x0E, 4, 1, 0x0C, 0x20, 0]))
+
+    data = service_data(8, pack(basic_id(), self_id()))
+    # non-connectable, data status "more to come"
+    report = extended_advertising_report(0x20, BLE_ADDRESS, data[:20], -71)
+    out += btsnoop_record(3, 374_000, le_meta(0x0D, bytes([1]) + report))
+    report = extended_advertising_report(0x00, BLE_ADDRESS, data, -71)
+    out += btsnoop_record(3, 375_000, le_meta(0x0D, bytes([1]) + report))
+
+    out += btsnoop_record(5, 400_000, bytes([1, 0x20, 4, 0, 0, 0, 4, 0]))
+    return out
+
+
+KECCAK_ROUND_CONSTANTS = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+]
+KECCAK_ROTATIONS = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+]
+SHAKE128_RATE = 168
+
+
+def rotate_left(lane, n):
+    return ((lane << n) | (lane >> (64 - n))) & (2**64 - 1) if n else lane
+
+
+def keccak_f(a):
+    for constant in KECCAK_ROUND_CONSTANTS:
+        c = [a[x][0] ^ a[x][1] ^ a[x][2] ^ a[x][3] ^ a[x][4] for x in range(5)]
+        d = [c[(x - 1) % 5] ^ rotate_left(c[(x + 1) % 5], 1) for x in range(5)]
+        a = [[a[x][y] ^ d[x] for y in range(5)] for x in range(5)]
+        b = [[0] * 5 for _ in range(5)]
+        for x in range(5):
+            for y in range(5):
+                b[y][(2 * x + 3 * y) % 5] = rotate_left(a[x][y], KECCAK_ROTATIONS[x][y])
+        a = [
+            [b[x][y] ^ (~b[(x + 1) % 5][y] & b[(x + 2) % 5][y]) for y in range(5)]
+            for x in range(5)
+        ]
+        a[0][0] ^= constant
+    return a
+
+
+def keccak128(data, suffix, size):
+    data = bytearray(data) + bytes([suffix])
+    data += bytes(-len(data) % SHAKE128_RATE)
+    data[-1] |= 0x80
+
+    lanes = SHAKE128_RATE // 8
+    a = [[0] * 5 for _ in range(5)]
+    for offset in range(0, len(data), SHAKE128_RATE):
+        for i in range(lanes):
+            start = offset + 8 * i
+            a[i % 5][i // 5] ^= int.from_bytes(data[start : start + 8], "little")
+        a = keccak_f(a)
+
+    out = b""
+    while len(out) < size:
+        out += b"".join(a[i % 5][i // 5].to_bytes(8, "little") for i in range(lanes))
+        a = keccak_f(a)
+    return out[:size]
+
+
+def left_encode(n):
+    raw = n.to_bytes(max(1, (n.bit_length() + 7) // 8), "big")
+    return bytes([len(raw)]) + raw
+
+
+def encode_string(s):
+    return left_encode(8 * len(s)) + s
+
+
+def cshake128(data, customization, size):
+    prefix = left_encode(SHAKE128_RATE) + encode_string(b"") + encode_string(customization)
+    prefix += bytes(-len(prefix) % SHAKE128_RATE)
+    return keccak128(prefix + data, 0x04, size)
+
+
+def check_cshake128():
+    # the permutation and padding against SHAKE128, then the NIST SP 800-185 samples 1 and 2
+    for data in [b"", b"abc", bytes(range(200)) * 2]:
+        assert keccak128(data, 0x1F, 200) == hashlib.shake_128(data).digest(200)
+
+    sample = cshake128(bytes(range(4)), b"Email Signature", 32)
+    assert sample.hex() == "c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5"
+    sample = cshake128(bytes(range(200)), b"Email Signature", 32)
+    assert sample.hex() == "c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b"
+
+
+HHIT_CONTEXT_ID = bytes.fromhex("00b5a69c795df5d5f0087f56843f2c40")
+RFC8032_TEST_1_PUBLIC_KEY = bytes.fromhex(
+    "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+)
+
+
+def hhit(raa, hda, public_key):
+    # prefix, RAA, HDA and suite ID (EdDSA/cSHAKE128), 68 bits padded to 9 bytes
+    tag = 0x2001003 << 40 | raa << 26 | hda << 8 | 5
+    # EdDSA host identity of curve Ed25519
+    host_identity = struct.pack(">H", 1) + public_key
+
+    digest = cshake128((tag << 4).to_bytes(9, "big") + host_identity, HHIT_CONTEXT_ID, 8)
+    hash_ = int.from_bytes(digest, "big") >> 4
+    return (tag << 60 | hash_).to_bytes(16, "big")
+
+
+if __name__ == "__main__":
+    check_cshake128()
+
+    here = Path(__file__).parent
+    (here / "remote_id.pcapng").write_bytes(pcapng())
+    (here / "remote_id.pcap").write_bytes(pcap())
+    (here / "remote_id.btsnoop").write_bytes(btsnoop())
+    (here / "hhit_ed25519.bin").write_bytes(hhit(10, 20, RFC8032_TEST_1_PUBLIC_KEY))